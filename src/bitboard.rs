@@ -0,0 +1,105 @@
+use std::ops::*;
+
+use crate::square::Square;
+
+/// A set of squares, one bit per square in [`Square::index`] order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Self = Self(0);
+    pub const FULL: Self = Self(!0);
+
+    pub const FILE_A: Self = Self(0x0101_0101_0101_0101);
+    pub const FILE_H: Self = Self(0x8080_8080_8080_8080);
+    pub const RANK_1: Self = Self(0xff);
+    pub const RANK_8: Self = Self(0xff << 56);
+
+    #[inline(always)]
+    pub const fn from_square(square: Square) -> Self {
+        Self(1 << square.index())
+    }
+
+    #[inline(always)]
+    pub const fn contains(&self, square: Square) -> bool {
+        self.0 & (1 << square.index()) != 0
+    }
+
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline(always)]
+    pub const fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// The lowest square in the set.
+    #[inline(always)]
+    pub fn first(&self) -> Option<Square> {
+        Square::from_index(self.0.trailing_zeros() as u8)
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = Squares;
+
+    fn into_iter(self) -> Squares {
+        Squares(self.0)
+    }
+}
+
+/// Yields the squares of a [`Bitboard`] from a1 to h8.
+pub struct Squares(u64);
+
+impl Iterator for Squares {
+    type Item = Square;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Square> {
+        let square = Square::from_index(self.0.trailing_zeros() as u8)?;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Self {
+        Self::from_square(square)
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($op:ident, $f:ident, $op_assign:ident, $f_assign:ident) => {
+        impl $op for Bitboard {
+            type Output = Self;
+
+            #[inline(always)]
+            fn $f(self, rhs: Self) -> Self {
+                Self($op::$f(self.0, rhs.0))
+            }
+        }
+
+        impl $op_assign for Bitboard {
+            #[inline(always)]
+            fn $f_assign(&mut self, rhs: Self) {
+                $op_assign::$f_assign(&mut self.0, rhs.0)
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+impl_bit_op!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+impl Not for Bitboard {
+    type Output = Self;
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
@@ -0,0 +1,275 @@
+//! Opening books in the Polyglot `.bin` format.
+//!
+//! A book is a list of 16-byte big-endian entries sorted by key: the
+//! position's Zobrist key, the move, a weight and four bytes of learning
+//! data the format never settled on. Keys come from [`Position::hash`],
+//! which is Polyglot's own key, so books made by other tools can be read.
+
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::path::Path;
+use std::{fs, io};
+
+use crate::moves::Move;
+use crate::piece::Piece;
+use crate::position::Position;
+use crate::square::Square;
+
+const ENTRY_SIZE: usize = 16;
+
+/// How to pick among the book moves for a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Selection {
+    /// Always the move with the highest weight.
+    Best,
+    /// At random, in proportion to the weights.
+    #[default]
+    Weighted,
+}
+
+/// A move the book knows for a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookMove {
+    pub mv: Move,
+    pub weight: u16,
+}
+
+#[derive(Debug)]
+pub enum BookError {
+    Io(io::Error),
+    /// The file is not a whole number of entries long.
+    Truncated {
+        len: usize,
+    },
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Truncated { len } => {
+                write!(f, "{len} bytes is not a whole number of book entries")
+            }
+        }
+    }
+}
+
+impl Error for BookError {}
+
+impl From<io::Error> for BookError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    key: u64,
+    mv: u16,
+    weight: u16,
+    learn: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Book {
+    /// Sorted by key, so that lookups can binary search.
+    entries: Vec<Entry>,
+}
+
+impl Book {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BookError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BookError> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(BookError::Truncated { len: bytes.len() });
+        }
+
+        let mut entries: Vec<Entry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|b| Entry {
+                key: u64::from_be_bytes(b[0..8].try_into().unwrap()),
+                mv: u16::from_be_bytes(b[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(b[10..12].try_into().unwrap()),
+                learn: u32::from_be_bytes(b[12..16].try_into().unwrap()),
+            })
+            .collect();
+
+        // Well-formed books are sorted already; a stable sort keeps the
+        // order of moves within a position
+        entries.sort_by_key(|entry| entry.key);
+
+        Ok(Self { entries })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * ENTRY_SIZE);
+
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.key.to_be_bytes());
+            bytes.extend_from_slice(&entry.mv.to_be_bytes());
+            bytes.extend_from_slice(&entry.weight.to_be_bytes());
+            bytes.extend_from_slice(&entry.learn.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds `weight` to the entry for `mv` in `position`, creating it if
+    /// need be. Weights saturate rather than wrap.
+    pub fn add(&mut self, position: &Position, mv: Move, weight: u16) {
+        let key = position.hash();
+        let raw = encode(position, mv);
+        let range = self.range(key);
+
+        match self.entries[range.clone()].iter_mut().find(|e| e.mv == raw) {
+            Some(entry) => entry.weight = entry.weight.saturating_add(weight),
+            None => self.entries.insert(
+                range.end,
+                Entry {
+                    key,
+                    mv: raw,
+                    weight,
+                    learn: 0,
+                },
+            ),
+        }
+    }
+
+    /// The legal book moves for `position`, heaviest first.
+    pub fn moves(&self, position: &Position) -> Vec<BookMove> {
+        let legal = position.legal_moves();
+
+        let mut moves: Vec<BookMove> = self.entries[self.range(position.hash())]
+            .iter()
+            .filter_map(|entry| {
+                let mv = decode(position, entry.mv);
+
+                // A key collision or a corrupt entry can name any move
+                let mv = legal.iter().copied().find(|&legal| legal == mv)?;
+
+                Some(BookMove {
+                    mv,
+                    weight: entry.weight,
+                })
+            })
+            .collect();
+
+        moves.sort_by_key(|book| std::cmp::Reverse(book.weight));
+        moves
+    }
+
+    /// Picks a book move for `position`, if the book has one.
+    pub fn choose(&self, position: &Position, selection: Selection) -> Option<Move> {
+        let random = RandomState::new().build_hasher().finish();
+        self.choose_with(position, selection, random)
+    }
+
+    /// Like [`Book::choose`], with the random number for weighted selection
+    /// supplied.
+    pub fn choose_with(
+        &self,
+        position: &Position,
+        selection: Selection,
+        random: u64,
+    ) -> Option<Move> {
+        let moves = self.moves(position);
+
+        match selection {
+            Selection::Best => moves.first().map(|book| book.mv),
+
+            Selection::Weighted => {
+                let total: u64 = moves.iter().map(|book| book.weight as u64).sum();
+
+                // All weights zero: nothing is recommended over anything else
+                if total == 0 {
+                    return moves.first().map(|book| book.mv);
+                }
+
+                let mut roll = random % total;
+
+                moves.into_iter().find_map(|book| {
+                    if roll < book.weight as u64 {
+                        Some(book.mv)
+                    } else {
+                        roll -= book.weight as u64;
+                        None
+                    }
+                })
+            }
+        }
+    }
+
+    /// The entries for `key`.
+    fn range(&self, key: u64) -> std::ops::Range<usize> {
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let end = self.entries.partition_point(|entry| entry.key <= key);
+
+        start..end
+    }
+}
+
+/// Polyglot packs a move into 16 bits: the to square, the from square, then
+/// the promotion piece. Castling is written as the king taking its own rook.
+fn encode(position: &Position, mv: Move) -> u16 {
+    let to = match position.piece_at(mv.from) {
+        Some((Piece::King, _)) if mv.from.file().abs_diff(mv.to.file()) == 2 => {
+            let rook_file = if mv.to.file() == 6 { 7 } else { 0 };
+            Square::new(rook_file, mv.from.rank())
+        }
+
+        _ => mv.to,
+    };
+
+    let promotion = match mv.promotion {
+        Some(Piece::Knight) => 1,
+        Some(Piece::Bishop) => 2,
+        Some(Piece::Rook) => 3,
+        Some(Piece::Queen) => 4,
+
+        _ => 0,
+    };
+
+    to.index() as u16 | (mv.from.index() as u16) << 6 | promotion << 12
+}
+
+fn decode(position: &Position, raw: u16) -> Move {
+    let square = |bits: u16| Square::from_index((bits & 0x3f) as u8).unwrap();
+    let from = square(raw >> 6);
+    let mut to = square(raw);
+
+    let promotion = match (raw >> 12) & 0x7 {
+        1 => Some(Piece::Knight),
+        2 => Some(Piece::Bishop),
+        3 => Some(Piece::Rook),
+        4 => Some(Piece::Queen),
+
+        _ => None,
+    };
+
+    // A king "taking" its own rook is castling
+    if let (Some((Piece::King, color)), Some((Piece::Rook, rook_color))) =
+        (position.piece_at(from), position.piece_at(to))
+    {
+        if color == rook_color {
+            let file = if to.file() > from.file() { 6 } else { 2 };
+            to = Square::new(file, from.rank());
+        }
+    }
+
+    Move {
+        promotion,
+        ..Move::new(from, to)
+    }
+}
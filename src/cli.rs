@@ -0,0 +1,174 @@
+//! Subcommands that run without opening a window.
+
+use std::process::ExitCode;
+
+use chess::consts::START_FEN;
+use chess::{parse_pgn, Book, Evaluation, PieceColor, Position};
+
+/// Runs the subcommand named by `args[0]`, or returns `None` if there is no
+/// such subcommand.
+pub fn run(args: &[String]) -> Option<ExitCode> {
+    let (command, args) = args.split_first()?;
+
+    Some(match command.as_str() {
+        "perft" => perft(args),
+        "eval" => eval(args),
+        "book" => book(args),
+        "--uci" => {
+            crate::uci::run();
+            ExitCode::SUCCESS
+        }
+
+        _ => return None,
+    })
+}
+
+pub fn usage() -> &'static str {
+    "usage: chess [--book book.bin] [--clock 5+3] [game.pgn]\n       chess --uci\n       chess perft <fen|startpos> <depth>\n       chess eval <fen|startpos>\n       \
+     chess book build <games.pgn> <book.bin> [plies]\n       \
+     chess book probe <book.bin> <fen|startpos>"
+}
+
+/// `chess perft <fen> <depth>`: prints the node count below every legal move
+/// and the total.
+fn perft(args: &[String]) -> ExitCode {
+    let [fen, depth] = args else {
+        eprintln!("{}", usage());
+        return ExitCode::from(2);
+    };
+
+    let Some(position) = parse_position(fen) else {
+        return ExitCode::from(2);
+    };
+
+    let Ok(depth) = depth.parse::<u32>() else {
+        eprintln!("Invalid depth: {depth}");
+        return ExitCode::from(2);
+    };
+
+    // The root alone is the one node at depth 0
+    let mut total = if depth == 0 { 1 } else { 0 };
+
+    for (mv, nodes) in position.divide(depth) {
+        println!("{mv}: {nodes}");
+        total += nodes;
+    }
+
+    println!("\nNodes searched: {total}");
+
+    ExitCode::SUCCESS
+}
+
+/// `chess eval <fen>`: prints every evaluation term, from White's point of
+/// view, and the total for the side to move.
+fn eval(args: &[String]) -> ExitCode {
+    // A FEN is several words, so take them all
+    if args.is_empty() {
+        eprintln!("{}", usage());
+        return ExitCode::from(2);
+    }
+
+    let Some(position) = parse_position(&args.join(" ")) else {
+        return ExitCode::from(2);
+    };
+
+    let eval = Evaluation::new(&position);
+    let side = position.side_to_move();
+    let for_side = match side {
+        PieceColor::White => eval.total(),
+        PieceColor::Black => -eval.total(),
+    };
+
+    println!("{eval}");
+    println!("\n{side:?} to move: {for_side:+}");
+
+    ExitCode::SUCCESS
+}
+
+/// How many plies of each game `chess book build` takes by default.
+const BOOK_PLIES: usize = 20;
+
+/// `chess book build <games.pgn> <book.bin> [plies]` counts the opening
+/// moves of every game into a book; `chess book probe <book.bin> <fen>`
+/// lists the book moves for a position.
+fn book(args: &[String]) -> ExitCode {
+    match args {
+        [command, pgn, out, rest @ ..] if command == "build" && rest.len() <= 1 => {
+            let plies = match rest.first().map(|p| p.parse()) {
+                None => BOOK_PLIES,
+                Some(Ok(plies)) => plies,
+                Some(Err(_)) => {
+                    eprintln!("Invalid ply count: {}", rest[0]);
+                    return ExitCode::from(2);
+                }
+            };
+
+            build_book(pgn, out, plies)
+        }
+
+        [command, path, fen @ ..] if command == "probe" && !fen.is_empty() => {
+            let Some(position) = parse_position(&fen.join(" ")) else {
+                return ExitCode::from(2);
+            };
+
+            let book = match Book::open(path) {
+                Ok(book) => book,
+                Err(e) => {
+                    eprintln!("Could not read {path}: {e}");
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            for book_move in book.moves(&position) {
+                println!("{} {}", book_move.mv.to_san(&position), book_move.weight);
+            }
+
+            ExitCode::SUCCESS
+        }
+
+        _ => {
+            eprintln!("{}", usage());
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn build_book(pgn: &str, out: &str, plies: usize) -> ExitCode {
+    let games = match std::fs::read_to_string(pgn)
+        .map_err(|e| e.to_string())
+        .and_then(|text| parse_pgn(&text).map_err(|e| e.to_string()))
+    {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("Could not read {pgn}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut book = Book::default();
+
+    for game in &games {
+        let mut position = game.start.clone();
+
+        for pgn_move in game.moves.iter().take(plies) {
+            book.add(&position, pgn_move.mv, 1);
+            position.make_move(pgn_move.mv);
+        }
+    }
+
+    if let Err(e) = std::fs::write(out, book.to_bytes()) {
+        eprintln!("Could not write {out}: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    println!("{} entries from {} games", book.len(), games.len());
+    ExitCode::SUCCESS
+}
+
+fn parse_position(fen: &str) -> Option<Position> {
+    let fen = if fen == "startpos" { START_FEN } else { fen };
+
+    Position::from_fen(fen)
+        .inspect_err(|e| eprintln!("Invalid FEN: {e}"))
+        .ok()
+}
@@ -0,0 +1,348 @@
+//! Chess clocks.
+//!
+//! A [`TimeControl`] is a list of stages, each giving some time for a
+//! number of moves (or the rest of the game) and a bonus earned on every
+//! move. A [`Clock`] runs one for both players. It reads the time through a
+//! [`TimeSource`], so that it can be driven by hand rather than by the wall
+//! clock.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::piece::PieceColor;
+
+/// Where a clock gets the time from: any steadily increasing measure from a
+/// fixed starting point.
+pub trait TimeSource {
+    fn now(&self) -> Duration;
+}
+
+/// Real time, since the source was made.
+#[derive(Debug, Clone, Copy)]
+pub struct WallClock {
+    start: Instant,
+}
+
+impl Default for WallClock {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl TimeSource for WallClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// What a player earns on each move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bonus {
+    #[default]
+    None,
+    /// Fischer: the increment is added after every move.
+    Increment(Duration),
+    /// Bronstein: the time the move took is given back, up to the delay.
+    Bronstein(Duration),
+    /// Simple (US) delay: the clock only starts counting down once the
+    /// delay has passed.
+    Delay(Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stage {
+    /// The moves to be made in `time`, or `None` for the rest of the game.
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub bonus: Bonus,
+}
+
+/// The stages of a time control, in order. Once the last one is reached it
+/// repeats, if it is for a number of moves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControl {
+    pub stages: Vec<Stage>,
+}
+
+impl TimeControl {
+    pub fn sudden_death(time: Duration) -> Self {
+        Self::fischer(time, Duration::ZERO)
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        let bonus = if increment.is_zero() {
+            Bonus::None
+        } else {
+            Bonus::Increment(increment)
+        };
+
+        Self {
+            stages: vec![Stage {
+                moves: None,
+                time,
+                bonus,
+            }],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimeControlError {
+    pub stage: String,
+}
+
+impl fmt::Display for ParseTimeControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid time control stage \"{}\"", self.stage)
+    }
+}
+
+impl Error for ParseTimeControlError {}
+
+/// Stages separated by `:`, each `[moves/]minutes[<bonus>seconds]` where
+/// the bonus is `+` for an increment, `d` for a simple delay or `b` for a
+/// Bronstein delay: `5+3`, `15d5`, `40/90+30:30+30`.
+impl FromStr for TimeControl {
+    type Err = ParseTimeControlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let stages = s
+            .split(':')
+            .map(|stage| {
+                parse_stage(stage.trim()).ok_or_else(|| ParseTimeControlError {
+                    stage: stage.into(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { stages })
+    }
+}
+
+fn parse_stage(s: &str) -> Option<Stage> {
+    let (moves, rest) = match s.split_once('/') {
+        Some((moves, rest)) => (Some(moves.parse().ok().filter(|&n| n > 0)?), rest),
+        None => (None, s),
+    };
+
+    // Negative, infinite and overflowing times are all refused here
+    let seconds = |s: &str| {
+        s.parse::<f64>()
+            .ok()
+            .and_then(|n| Duration::try_from_secs_f64(n).ok())
+    };
+
+    let (time, bonus) = match rest.find(['+', 'd', 'b']) {
+        Some(at) => {
+            let bonus = seconds(&rest[at + 1..])?;
+
+            let bonus = match &rest[at..at + 1] {
+                "+" => Bonus::Increment(bonus),
+                "d" => Bonus::Delay(bonus),
+                _ => Bonus::Bronstein(bonus),
+            };
+
+            (&rest[..at], bonus)
+        }
+
+        None => (rest, Bonus::None),
+    };
+
+    Some(Stage {
+        moves,
+        time: seconds(time)?.checked_mul(60)?,
+        bonus,
+    })
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stage) in self.stages.iter().enumerate() {
+            if i > 0 {
+                f.write_str(":")?;
+            }
+
+            if let Some(moves) = stage.moves {
+                write!(f, "{moves}/")?;
+            }
+
+            write!(f, "{}", stage.time.as_secs_f64() / 60.)?;
+
+            match stage.bonus {
+                Bonus::None => {}
+                Bonus::Increment(d) => write!(f, "+{}", d.as_secs_f64())?,
+                Bonus::Delay(d) => write!(f, "d{}", d.as_secs_f64())?,
+                Bonus::Bronstein(d) => write!(f, "b{}", d.as_secs_f64())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A clock for both players.
+///
+/// Nothing runs until [`Clock::start`]; from then on each [`Clock::press`]
+/// ends the running player's move and starts the other's.
+#[derive(Debug, Clone)]
+pub struct Clock<T = WallClock> {
+    control: TimeControl,
+    source: T,
+    remaining: [Duration; 2],
+    /// Index into the stages, for each player.
+    stage: [usize; 2],
+    /// Moves made in the current stage, for each player.
+    stage_moves: [u32; 2],
+    /// The player whose time is running, and when their move began.
+    running: Option<(PieceColor, Duration)>,
+    flagged: Option<PieceColor>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        Self::with_source(control, WallClock::default())
+    }
+}
+
+impl<T: TimeSource> Clock<T> {
+    /// # Panics
+    ///
+    /// If `control` has no stages.
+    pub fn with_source(control: TimeControl, source: T) -> Self {
+        let time = control.stages[0].time;
+
+        Self {
+            control,
+            source,
+            remaining: [time; 2],
+            stage: [0; 2],
+            stage_moves: [0; 2],
+            running: None,
+            flagged: None,
+        }
+    }
+
+    pub fn control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    /// The stage `color` is playing in.
+    pub fn stage(&self, color: PieceColor) -> &Stage {
+        &self.control.stages[self.stage[color as usize]]
+    }
+
+    /// The moves `color` has left to make before the next time control.
+    pub fn moves_to_go(&self, color: PieceColor) -> Option<u32> {
+        let made = self.stage_moves[color as usize];
+        self.stage(color).moves.map(|moves| moves - made)
+    }
+
+    /// The player whose time is running.
+    pub fn active(&self) -> Option<PieceColor> {
+        self.running.map(|(color, _)| color)
+    }
+
+    /// The time `color` has left, counting the move in progress.
+    pub fn remaining(&self, color: PieceColor) -> Duration {
+        let remaining = self.remaining[color as usize];
+
+        match self.running {
+            Some((active, since)) if active == color => {
+                remaining.saturating_sub(self.charge(color, self.source.now() - since))
+            }
+
+            _ => remaining,
+        }
+    }
+
+    /// The player who has run out of time, if either has.
+    pub fn flagged(&self) -> Option<PieceColor> {
+        self.flagged.or_else(|| {
+            self.active()
+                .filter(|&color| self.remaining(color).is_zero())
+        })
+    }
+
+    /// Starts `color`'s time, stopping the other player's without ending
+    /// their move.
+    pub fn start(&mut self, color: PieceColor) {
+        self.pause();
+
+        if self.flagged.is_none() {
+            self.running = Some((color, self.source.now()));
+        }
+    }
+
+    /// Stops the running time, charging what has been used so far. A simple
+    /// delay starts afresh when the time is started again.
+    pub fn pause(&mut self) {
+        if let Some((color, since)) = self.running.take() {
+            self.stop(color, since);
+        }
+    }
+
+    /// Ends the running player's move: they earn their bonus, move on to
+    /// the next stage if that was the last move of this one, and the other
+    /// player's time starts. A player whose flag has already fallen earns
+    /// nothing and the clock stops.
+    pub fn press(&mut self) {
+        let Some((color, since)) = self.running.take() else {
+            return;
+        };
+
+        let used = self.stop(color, since);
+
+        if self.flagged.is_some() {
+            return;
+        }
+
+        let i = color as usize;
+
+        match self.stage(color).bonus {
+            Bonus::Increment(increment) => self.remaining[i] += increment,
+            Bonus::Bronstein(delay) => self.remaining[i] += used.min(delay),
+            Bonus::None | Bonus::Delay(_) => {}
+        }
+
+        self.stage_moves[i] += 1;
+
+        if self.stage(color).moves == Some(self.stage_moves[i]) {
+            let last = self.control.stages.len() - 1;
+
+            self.stage[i] = (self.stage[i] + 1).min(last);
+            self.stage_moves[i] = 0;
+            self.remaining[i] += self.stage(color).time;
+        }
+
+        self.running = Some((color.opposite(), self.source.now()));
+    }
+
+    /// Charges `color` for their time since `since`, returning how long
+    /// that was.
+    fn stop(&mut self, color: PieceColor, since: Duration) -> Duration {
+        let used = self.source.now() - since;
+        let charge = self.charge(color, used);
+        let remaining = &mut self.remaining[color as usize];
+
+        if charge >= *remaining {
+            *remaining = Duration::ZERO;
+            self.flagged = Some(color);
+        } else {
+            *remaining -= charge;
+        }
+
+        used
+    }
+
+    /// How much of `used` comes off `color`'s time.
+    fn charge(&self, color: PieceColor, used: Duration) -> Duration {
+        match self.stage(color).bonus {
+            Bonus::Delay(delay) => used.saturating_sub(delay),
+            _ => used,
+        }
+    }
+}
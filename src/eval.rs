@@ -0,0 +1,504 @@
+//! Static evaluation.
+//!
+//! Every term is worked out twice, once for the middlegame and once for the
+//! endgame, and the two are blended by how much material is left, so that
+//! (for example) the king hides early on and marches to the centre late.
+
+use std::fmt;
+
+use crate::attacks;
+use crate::bitboard::Bitboard;
+use crate::piece::{Piece, PieceColor};
+use crate::position::Position;
+use crate::square::Square;
+
+/// The game phase with all minor and major pieces on the board.
+pub const MAX_PHASE: i32 = 24;
+
+const COLORS: [PieceColor; 2] = [PieceColor::White, PieceColor::Black];
+
+/// A middlegame and an endgame score, in centipawns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Term {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Term {
+    const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    /// Blends the two scores for a game `phase` between 0 (bare endgame)
+    /// and [`MAX_PHASE`].
+    pub fn tapered(&self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl std::ops::Add for Term {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.mg + rhs.mg, self.eg + rhs.eg)
+    }
+}
+
+impl std::ops::Sub for Term {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.mg - rhs.mg, self.eg - rhs.eg)
+    }
+}
+
+impl std::ops::Mul<i32> for Term {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self {
+        Self::new(self.mg * rhs, self.eg * rhs)
+    }
+}
+
+impl std::ops::AddAssign for Term {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// The evaluation of a position split into its terms, each from White's
+/// point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Evaluation {
+    pub material: Term,
+    /// Piece-square tables.
+    pub placement: Term,
+    /// Doubled, isolated and passed pawns.
+    pub pawns: Term,
+    pub mobility: Term,
+    pub king_safety: Term,
+    /// From 0, bare kings and pawns, up to [`MAX_PHASE`].
+    pub phase: i32,
+}
+
+impl Evaluation {
+    pub fn new(position: &Position) -> Self {
+        let side = |color| {
+            let mut eval = Self {
+                material: material(position, color),
+                placement: placement(position, color),
+                pawns: pawn_structure(position, color),
+                mobility: mobility(position, color),
+                king_safety: king_safety(position, color),
+                phase: 0,
+            };
+
+            if color == PieceColor::Black {
+                eval = eval.negated();
+            }
+
+            eval
+        };
+
+        let white = side(PieceColor::White);
+        let black = side(PieceColor::Black);
+
+        Self {
+            material: white.material + black.material,
+            placement: white.placement + black.placement,
+            pawns: white.pawns + black.pawns,
+            mobility: white.mobility + black.mobility,
+            king_safety: white.king_safety + black.king_safety,
+            phase: phase(position),
+        }
+    }
+
+    /// The terms with their names, in the order they are reported.
+    pub fn terms(&self) -> [(&'static str, Term); 5] {
+        [
+            ("Material", self.material),
+            ("Placement", self.placement),
+            ("Pawns", self.pawns),
+            ("Mobility", self.mobility),
+            ("King safety", self.king_safety),
+        ]
+    }
+
+    /// The whole evaluation from White's point of view, in centipawns.
+    pub fn total(&self) -> i32 {
+        let sum = self
+            .terms()
+            .into_iter()
+            .fold(Term::default(), |sum, (_, term)| sum + term);
+
+        sum.tapered(self.phase)
+    }
+
+    fn negated(self) -> Self {
+        let zero = Term::default();
+
+        Self {
+            material: zero - self.material,
+            placement: zero - self.placement,
+            pawns: zero - self.pawns,
+            mobility: zero - self.mobility,
+            king_safety: zero - self.king_safety,
+            phase: self.phase,
+        }
+    }
+}
+
+/// A table of every term, as printed by `chess eval`.
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<12} {:>7} {:>7} {:>7}", "Term", "MG", "EG", "Blended")?;
+
+        for (name, term) in self.terms() {
+            writeln!(
+                f,
+                "{name:<12} {:>7} {:>7} {:>7}",
+                term.mg,
+                term.eg,
+                term.tapered(self.phase)
+            )?;
+        }
+
+        writeln!(
+            f,
+            "Phase        {:>7}",
+            format!("{}/{MAX_PHASE}", self.phase)
+        )?;
+        write!(f, "Total        {:>23}", self.total())
+    }
+}
+
+/// The evaluation of `position` in centipawns, from the point of view of
+/// the side to move.
+pub fn evaluate(position: &Position) -> i32 {
+    let eval = Evaluation::new(position).total();
+
+    match position.side_to_move() {
+        PieceColor::White => eval,
+        PieceColor::Black => -eval,
+    }
+}
+
+fn phase(position: &Position) -> i32 {
+    let weights = [
+        (Piece::Knight, 1),
+        (Piece::Bishop, 1),
+        (Piece::Rook, 2),
+        (Piece::Queen, 4),
+    ];
+
+    let phase: i32 = weights
+        .iter()
+        .flat_map(|&(piece, weight)| {
+            COLORS.map(|color| position.pieces(piece, color).count() as i32 * weight)
+        })
+        .sum();
+
+    // Promotions can push it past the starting total
+    phase.min(MAX_PHASE)
+}
+
+fn material(position: &Position, color: PieceColor) -> Term {
+    let value: i32 = [
+        Piece::Queen,
+        Piece::Rook,
+        Piece::Bishop,
+        Piece::Knight,
+        Piece::Pawn,
+    ]
+    .iter()
+    .map(|piece| position.pieces(*piece, color).count() as i32 * piece.value())
+    .sum();
+
+    Term::new(value, value)
+}
+
+/// Piece-square tables, from White's side of the board with a8 first so
+/// that they read like a diagram. Black uses them mirrored.
+#[rustfmt::skip]
+mod tables {
+    pub const PAWN_MG: [i32; 64] = [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         10,  10,  20,  30,  30,  20,  10,  10,
+          5,   5,  10,  25,  25,  10,   5,   5,
+          0,   0,   0,  20,  20,   0,   0,   0,
+          5,  -5, -10,   0,   0, -10,  -5,   5,
+          5,  10,  10, -20, -20,  10,  10,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ];
+
+    pub const PAWN_EG: [i32; 64] = [
+          0,   0,   0,   0,   0,   0,   0,   0,
+         80,  80,  80,  80,  80,  80,  80,  80,
+         50,  50,  50,  50,  50,  50,  50,  50,
+         30,  30,  30,  30,  30,  30,  30,  30,
+         15,  15,  15,  15,  15,  15,  15,  15,
+          5,   5,   5,   5,   5,   5,   5,   5,
+          0,   0,   0,   0,   0,   0,   0,   0,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ];
+
+    pub const KNIGHT: [i32; 64] = [
+        -50, -40, -30, -30, -30, -30, -40, -50,
+        -40, -20,   0,   0,   0,   0, -20, -40,
+        -30,   0,  10,  15,  15,  10,   0, -30,
+        -30,   5,  15,  20,  20,  15,   5, -30,
+        -30,   0,  15,  20,  20,  15,   0, -30,
+        -30,   5,  10,  15,  15,  10,   5, -30,
+        -40, -20,   0,   5,   5,   0, -20, -40,
+        -50, -40, -30, -30, -30, -30, -40, -50,
+    ];
+
+    pub const BISHOP: [i32; 64] = [
+        -20, -10, -10, -10, -10, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,  10,  10,   5,   0, -10,
+        -10,   5,   5,  10,  10,   5,   5, -10,
+        -10,   0,  10,  10,  10,  10,   0, -10,
+        -10,  10,  10,  10,  10,  10,  10, -10,
+        -10,   5,   0,   0,   0,   0,   5, -10,
+        -20, -10, -10, -10, -10, -10, -10, -20,
+    ];
+
+    pub const ROOK_MG: [i32; 64] = [
+          0,   0,   0,   0,   0,   0,   0,   0,
+          5,  10,  10,  10,  10,  10,  10,   5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+         -5,   0,   0,   0,   0,   0,   0,  -5,
+          0,   0,   0,   5,   5,   0,   0,   0,
+    ];
+
+    pub const ROOK_EG: [i32; 64] = [0; 64];
+
+    pub const QUEEN: [i32; 64] = [
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+        -10,   0,   0,   0,   0,   0,   0, -10,
+        -10,   0,   5,   5,   5,   5,   0, -10,
+         -5,   0,   5,   5,   5,   5,   0,  -5,
+          0,   0,   5,   5,   5,   5,   0,  -5,
+        -10,   5,   5,   5,   5,   5,   0, -10,
+        -10,   0,   5,   0,   0,   0,   0, -10,
+        -20, -10, -10,  -5,  -5, -10, -10, -20,
+    ];
+
+    pub const KING_MG: [i32; 64] = [
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -30, -40, -40, -50, -50, -40, -40, -30,
+        -20, -30, -30, -40, -40, -30, -30, -20,
+        -10, -20, -20, -20, -20, -20, -20, -10,
+         20,  20,   0,   0,   0,   0,  20,  20,
+         20,  30,  10,   0,   0,  10,  30,  20,
+    ];
+
+    pub const KING_EG: [i32; 64] = [
+        -50, -40, -30, -20, -20, -30, -40, -50,
+        -30, -20, -10,   0,   0, -10, -20, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  30,  40,  40,  30, -10, -30,
+        -30, -10,  20,  30,  30,  20, -10, -30,
+        -30, -30,   0,   0,   0,   0, -30, -30,
+        -50, -30, -30, -30, -30, -30, -30, -50,
+    ];
+}
+
+fn placement(position: &Position, color: PieceColor) -> Term {
+    use tables::*;
+
+    let tables = [
+        (Piece::Pawn, &PAWN_MG, &PAWN_EG),
+        (Piece::Knight, &KNIGHT, &KNIGHT),
+        (Piece::Bishop, &BISHOP, &BISHOP),
+        (Piece::Rook, &ROOK_MG, &ROOK_EG),
+        (Piece::Queen, &QUEEN, &QUEEN),
+        (Piece::King, &KING_MG, &KING_EG),
+    ];
+
+    let mut term = Term::default();
+
+    for (piece, mg, eg) in tables {
+        for square in position.pieces(piece, color) {
+            // The tables start at a8, so White's ranks count down
+            let rank = relative_rank(square, color);
+            let index = (7 - rank as usize) * 8 + square.file() as usize;
+
+            term += Term::new(mg[index], eg[index]);
+        }
+    }
+
+    term
+}
+
+/// The rank as seen from `color`'s side of the board, 0 being its own back
+/// rank.
+fn relative_rank(square: Square, color: PieceColor) -> u8 {
+    match color {
+        PieceColor::White => square.rank(),
+        PieceColor::Black => 7 - square.rank(),
+    }
+}
+
+fn file_mask(file: u8) -> Bitboard {
+    Bitboard(Bitboard::FILE_A.0 << file)
+}
+
+/// The files either side of `file`.
+fn adjacent_files(file: u8) -> Bitboard {
+    let mut mask = Bitboard::EMPTY;
+
+    if file > 0 {
+        mask |= file_mask(file - 1);
+    }
+
+    if file < 7 {
+        mask |= file_mask(file + 1);
+    }
+
+    mask
+}
+
+/// Every square on a rank ahead of `square` from `color`'s point of view.
+fn ranks_ahead(square: Square, color: PieceColor) -> Bitboard {
+    match color {
+        PieceColor::White if square.rank() == 7 => Bitboard::EMPTY,
+        PieceColor::White => Bitboard(!0 << ((square.rank() as u32 + 1) * 8)),
+        PieceColor::Black => Bitboard((1u64 << (square.rank() as u32 * 8)) - 1),
+    }
+}
+
+const DOUBLED: Term = Term::new(-10, -20);
+const ISOLATED: Term = Term::new(-10, -15);
+
+/// By relative rank; a pawn on the seventh is one step from queening.
+const PASSED: [Term; 8] = [
+    Term::new(0, 0),
+    Term::new(5, 10),
+    Term::new(5, 15),
+    Term::new(10, 25),
+    Term::new(20, 45),
+    Term::new(35, 75),
+    Term::new(60, 120),
+    Term::new(0, 0),
+];
+
+fn pawn_structure(position: &Position, color: PieceColor) -> Term {
+    let ours = position.pieces(Piece::Pawn, color);
+    let theirs = position.pieces(Piece::Pawn, color.opposite());
+    let mut term = Term::default();
+
+    for file in 0..8 {
+        let count = (ours & file_mask(file)).count() as i32;
+
+        if count > 1 {
+            term += DOUBLED * (count - 1);
+        }
+    }
+
+    for square in ours {
+        let file = square.file();
+
+        if (ours & adjacent_files(file)).is_empty() {
+            term += ISOLATED;
+        }
+
+        let blockers = (file_mask(file) | adjacent_files(file)) & ranks_ahead(square, color);
+
+        if (theirs & blockers).is_empty() {
+            term += PASSED[relative_rank(square, color) as usize];
+        }
+    }
+
+    term
+}
+
+/// Per square a piece can move to, leaving out those guarded by enemy pawns.
+const MOBILITY: [(Piece, Term); 4] = [
+    (Piece::Knight, Term::new(4, 4)),
+    (Piece::Bishop, Term::new(5, 5)),
+    (Piece::Rook, Term::new(2, 4)),
+    (Piece::Queen, Term::new(1, 2)),
+];
+
+/// Squares attacked by `color`'s pawns.
+fn pawn_attacks(position: &Position, color: PieceColor) -> Bitboard {
+    position
+        .pieces(Piece::Pawn, color)
+        .into_iter()
+        .fold(Bitboard::EMPTY, |attacks, square| {
+            attacks | attacks::pawn_attacks(color, square)
+        })
+}
+
+fn mobility(position: &Position, color: PieceColor) -> Term {
+    let occupied = position.occupied();
+    let available = !position.color(color) & !pawn_attacks(position, color.opposite());
+    let mut term = Term::default();
+
+    for (piece, weight) in MOBILITY {
+        for square in position.pieces(piece, color) {
+            let moves = attacks::piece_attacks(piece, square, occupied) & available;
+            term += weight * moves.count() as i32;
+        }
+    }
+
+    term
+}
+
+const SHIELD_PAWN: Term = Term::new(12, 0);
+
+/// Per attack on a square next to the king.
+const KING_ZONE_ATTACKS: [(Piece, i32); 4] = [
+    (Piece::Knight, 2),
+    (Piece::Bishop, 2),
+    (Piece::Rook, 3),
+    (Piece::Queen, 5),
+];
+
+fn king_safety(position: &Position, color: PieceColor) -> Term {
+    let Some(king) = position.king_square(color) else {
+        return Term::default();
+    };
+
+    let mut term = Term::default();
+
+    // Pawns in front of a king still on its back two ranks shelter it
+    if relative_rank(king, color) <= 1 {
+        let files = file_mask(king.file()) | adjacent_files(king.file());
+        let front = files & ranks_ahead(king, color);
+        let near = front
+            & attacks::king_attacks(king)
+                .into_iter()
+                .fold(Bitboard::EMPTY, |near, square| {
+                    near | Bitboard::from_square(square) | attacks::king_attacks(square)
+                });
+
+        let shield = position.pieces(Piece::Pawn, color) & near;
+        term += SHIELD_PAWN * shield.count() as i32;
+    }
+
+    let zone = attacks::king_attacks(king) | Bitboard::from_square(king);
+    let occupied = position.occupied();
+    let mut danger = 0;
+
+    for (piece, weight) in KING_ZONE_ATTACKS {
+        for square in position.pieces(piece, color.opposite()) {
+            let hits = attacks::piece_attacks(piece, square, occupied) & zone;
+            danger += weight * hits.count() as i32;
+        }
+    }
+
+    // A lone attacker is little worry; several together grow dangerous
+    // fast. Once the queens are off the king can look after itself.
+    let penalty = danger * danger / 2;
+    term + Term::new(-penalty, 0)
+}
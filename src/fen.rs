@@ -0,0 +1,409 @@
+//! Forsyth–Edwards Notation import and export.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::piece::*;
+use crate::position::{CastlingRights, Position};
+use crate::square::Square;
+
+/// Why a FEN string was rejected. Every `index` is the byte offset of the
+/// offending character (or field) within the string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// A character in the placement field that is neither a piece letter,
+    /// a digit from 1 to 8, nor `/`.
+    InvalidPiece {
+        ch: char,
+        index: usize,
+    },
+    /// The placement field does not describe exactly eight ranks.
+    WrongRankCount {
+        found: usize,
+        index: usize,
+    },
+    /// A rank describes more than eight squares.
+    RankTooLong {
+        index: usize,
+    },
+    /// A rank describes fewer than eight squares.
+    RankTooShort {
+        index: usize,
+    },
+    InvalidSideToMove {
+        index: usize,
+    },
+    InvalidCastling {
+        index: usize,
+    },
+    InvalidEnPassant {
+        index: usize,
+    },
+    InvalidHalfmoveClock {
+        index: usize,
+    },
+    InvalidFullmoveNumber {
+        index: usize,
+    },
+    /// Something follows the sixth field.
+    UnexpectedField {
+        index: usize,
+    },
+    MissingKing {
+        color: PieceColor,
+    },
+    TooManyKings {
+        color: PieceColor,
+        index: usize,
+    },
+    /// A pawn on the first or eighth rank.
+    PawnOnBackRank {
+        index: usize,
+    },
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPiece { ch, index } => {
+                write!(f, "invalid piece '{ch}' at index {index}")
+            }
+            Self::WrongRankCount { found, index } => {
+                write!(f, "expected 8 ranks, found {found} (at index {index})")
+            }
+            Self::RankTooLong { index } => {
+                write!(f, "rank has more than 8 squares at index {index}")
+            }
+            Self::RankTooShort { index } => {
+                write!(f, "rank has fewer than 8 squares at index {index}")
+            }
+            Self::InvalidSideToMove { index } => {
+                write!(f, "invalid side to move at index {index}")
+            }
+            Self::InvalidCastling { index } => {
+                write!(f, "invalid castling rights at index {index}")
+            }
+            Self::InvalidEnPassant { index } => {
+                write!(f, "invalid en passant square at index {index}")
+            }
+            Self::InvalidHalfmoveClock { index } => {
+                write!(f, "invalid halfmove clock at index {index}")
+            }
+            Self::InvalidFullmoveNumber { index } => {
+                write!(f, "invalid fullmove number at index {index}")
+            }
+            Self::UnexpectedField { index } => {
+                write!(f, "unexpected extra field at index {index}")
+            }
+            Self::MissingKing { color } => write!(f, "{color:?} has no king"),
+            Self::TooManyKings { color, index } => {
+                write!(f, "{color:?} has more than one king (at index {index})")
+            }
+            Self::PawnOnBackRank { index } => {
+                write!(f, "pawn on the back rank at index {index}")
+            }
+        }
+    }
+}
+
+impl Error for FenError {}
+
+impl FromStr for Position {
+    type Err = FenError;
+
+    fn from_str(fen: &str) -> Result<Self, Self::Err> {
+        Self::from_fen(fen)
+    }
+}
+
+impl Position {
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let mut position = Self::empty();
+        position.parse_fen(fen)?;
+        Ok(position)
+    }
+
+    /// Replaces the whole position with the one described by `fen`. On error
+    /// the position is left untouched.
+    ///
+    /// Only the piece placement field is required; missing trailing fields
+    /// default to `w - - 0 1`.
+    pub fn parse_fen(&mut self, fen: &str) -> Result<(), FenError> {
+        let mut position = Self::empty();
+        let mut fields = split_fields(fen);
+
+        let (_, placement) = fields.next().unwrap_or((0, ""));
+        position.parse_placement(placement)?;
+
+        if let Some((index, side)) = fields.next() {
+            position.side_to_move = match side {
+                "w" => PieceColor::White,
+                "b" => PieceColor::Black,
+
+                _ => return Err(FenError::InvalidSideToMove { index }),
+            };
+        }
+
+        if let Some((index, castling)) = fields.next() {
+            let rights = parse_castling(castling)
+                .filter(|rights| castling_matches_pieces(&position, rights))
+                .ok_or(FenError::InvalidCastling { index })?;
+
+            position.set_castling_rights(rights);
+        }
+
+        if let Some((index, en_passant)) = fields.next() {
+            position.en_passant = match en_passant {
+                "-" => None,
+
+                s => {
+                    // The skipped square is behind the pawn that just moved:
+                    // it must be empty, with the pawn past it and nothing on
+                    // the square the pawn started from
+                    let mover = position.side_to_move.opposite();
+                    let (expected_rank, pawn_rank, start_rank) = match mover {
+                        PieceColor::Black => (5, 4, 6),
+                        PieceColor::White => (2, 3, 1),
+                    };
+
+                    let square = Square::from_algebraic(s)
+                        .filter(|square| square.rank() == expected_rank)
+                        .filter(|square| {
+                            let file = square.file();
+
+                            position.piece_at(*square).is_none()
+                                && position.piece_at(Square::new(file, pawn_rank))
+                                    == Some((Piece::Pawn, mover))
+                                && position.piece_at(Square::new(file, start_rank)).is_none()
+                        });
+
+                    Some(square.ok_or(FenError::InvalidEnPassant { index })?)
+                }
+            };
+        }
+
+        if let Some((index, halfmove)) = fields.next() {
+            position.halfmove_clock = halfmove
+                .parse()
+                .map_err(|_| FenError::InvalidHalfmoveClock { index })?;
+        }
+
+        if let Some((index, fullmove)) = fields.next() {
+            position.fullmove_number = fullmove
+                .parse()
+                .map_err(|_| FenError::InvalidFullmoveNumber { index })?;
+        }
+
+        if let Some((index, _)) = fields.next() {
+            return Err(FenError::UnexpectedField { index });
+        }
+
+        *self = position;
+
+        Ok(())
+    }
+
+    fn parse_placement(&mut self, placement: &str) -> Result<(), FenError> {
+        let mut rank = 7;
+        let mut file = 0;
+        let mut kings = [0; 2];
+
+        for (index, ch) in placement.char_indices() {
+            match ch {
+                '1'..='9' => {
+                    file += ch.to_digit(10).unwrap() as u8;
+
+                    if file > 8 {
+                        return Err(FenError::RankTooLong { index });
+                    }
+                }
+
+                '/' => {
+                    if file < 8 {
+                        return Err(FenError::RankTooShort { index });
+                    }
+
+                    if rank == 0 {
+                        return Err(FenError::WrongRankCount {
+                            found: placement.split('/').count(),
+                            index,
+                        });
+                    }
+
+                    file = 0;
+                    rank -= 1;
+                }
+
+                p => {
+                    let (piece, color) =
+                        Piece::from_char(p).ok_or(FenError::InvalidPiece { ch: p, index })?;
+
+                    if file >= 8 {
+                        return Err(FenError::RankTooLong { index });
+                    }
+
+                    match piece {
+                        Piece::Pawn if rank == 0 || rank == 7 => {
+                            return Err(FenError::PawnOnBackRank { index });
+                        }
+
+                        Piece::King => {
+                            kings[color as usize] += 1;
+
+                            if kings[color as usize] > 1 {
+                                return Err(FenError::TooManyKings { color, index });
+                            }
+                        }
+
+                        _ => {}
+                    }
+
+                    self.set_piece(Square::new(file, rank), Some((piece, color)));
+
+                    file += 1;
+                }
+            }
+        }
+
+        if rank != 0 {
+            return Err(FenError::WrongRankCount {
+                found: 8 - rank as usize,
+                index: placement.len(),
+            });
+        }
+
+        if file < 8 {
+            return Err(FenError::RankTooShort {
+                index: placement.len(),
+            });
+        }
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            if kings[color as usize] == 0 {
+                return Err(FenError::MissingKing { color });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Serialises the position as a full six-field FEN string.
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::with_capacity(90);
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+
+            for file in 0..8 {
+                match self.piece_at(Square::new(file, rank)) {
+                    Some((piece, color)) => {
+                        if empty > 0 {
+                            fen.push(char::from_digit(empty, 10).unwrap());
+                            empty = 0;
+                        }
+
+                        fen.push(piece.to_char(color));
+                    }
+
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                fen.push(char::from_digit(empty, 10).unwrap());
+            }
+
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push(' ');
+        fen.push(match self.side_to_move {
+            PieceColor::White => 'w',
+            PieceColor::Black => 'b',
+        });
+
+        fen.push(' ');
+        if self.castling_rights().is_empty() {
+            fen.push('-');
+        } else {
+            for (available, c) in [
+                (self.castling_rights().white_kingside, 'K'),
+                (self.castling_rights().white_queenside, 'Q'),
+                (self.castling_rights().black_kingside, 'k'),
+                (self.castling_rights().black_queenside, 'q'),
+            ] {
+                if available {
+                    fen.push(c);
+                }
+            }
+        }
+
+        fen.push(' ');
+        match self.en_passant {
+            Some(square) => fen.push_str(&square.to_string()),
+            None => fen.push('-'),
+        }
+
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+
+        fen
+    }
+}
+
+/// Splits `fen` on whitespace, keeping the byte offset of every field.
+fn split_fields(fen: &str) -> impl Iterator<Item = (usize, &str)> {
+    fen.split(|c: char| c.is_ascii_whitespace())
+        .filter(|field| !field.is_empty())
+        .map(move |field| (field.as_ptr() as usize - fen.as_ptr() as usize, field))
+}
+
+fn parse_castling(s: &str) -> Option<CastlingRights> {
+    let mut castling = CastlingRights::NONE;
+
+    if s == "-" {
+        return Some(castling);
+    }
+
+    for ch in s.chars() {
+        let right = match ch {
+            'K' => &mut castling.white_kingside,
+            'Q' => &mut castling.white_queenside,
+            'k' => &mut castling.black_kingside,
+            'q' => &mut castling.black_queenside,
+
+            _ => return None,
+        };
+
+        if *right {
+            return None;
+        }
+
+        *right = true;
+    }
+
+    (!s.is_empty()).then_some(castling)
+}
+
+/// Every right needs the king and the matching rook on their original squares.
+fn castling_matches_pieces(position: &Position, rights: &CastlingRights) -> bool {
+    let on = |file, rank, piece, color| {
+        position.piece_at(Square::new(file, rank)) == Some((piece, color))
+    };
+
+    [
+        (rights.white_kingside, 0, 7, PieceColor::White),
+        (rights.white_queenside, 0, 0, PieceColor::White),
+        (rights.black_kingside, 7, 7, PieceColor::Black),
+        (rights.black_queenside, 7, 0, PieceColor::Black),
+    ]
+    .into_iter()
+    .all(|(available, rank, rook_file, color)| {
+        !available || (on(4, rank, Piece::King, color) && on(rook_file, rank, Piece::Rook, color))
+    })
+}
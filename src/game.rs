@@ -0,0 +1,360 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::moves::Move;
+use crate::piece::PieceColor;
+use crate::position::Position;
+
+/// Where a game stands. Anything other than `InProgress` is final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameStatus {
+    InProgress,
+    Checkmate {
+        winner: PieceColor,
+    },
+    Stalemate,
+    Draw(DrawReason),
+    Resigned {
+        winner: PieceColor,
+    },
+    TimeForfeit {
+        winner: PieceColor,
+    },
+    /// A result decided away from the board, such as a PGN `Result` tag,
+    /// for a win when `winner` is set and a draw otherwise.
+    Recorded {
+        winner: Option<PieceColor>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DrawReason {
+    Agreement,
+    ThreefoldRepetition,
+    FivefoldRepetition,
+    FiftyMoveRule,
+    SeventyFiveMoveRule,
+    InsufficientMaterial,
+    /// A flag fell, but the other side could never have mated.
+    TimeoutVsInsufficientMaterial,
+}
+
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InProgress => write!(f, "In progress"),
+            Self::Checkmate { winner } => write!(f, "Checkmate, {winner:?} wins"),
+            Self::Stalemate => write!(f, "Draw by stalemate"),
+            Self::Draw(reason) => write!(f, "Draw by {reason}"),
+            Self::Resigned { winner } => {
+                write!(f, "{:?} resigned, {winner:?} wins", winner.opposite())
+            }
+            Self::TimeForfeit { winner } => {
+                write!(f, "{:?} lost on time, {winner:?} wins", winner.opposite())
+            }
+            Self::Recorded {
+                winner: Some(winner),
+            } => write!(f, "{winner:?} wins"),
+            Self::Recorded { winner: None } => write!(f, "Drawn"),
+        }
+    }
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Agreement => "agreement",
+            Self::ThreefoldRepetition => "threefold repetition",
+            Self::FivefoldRepetition => "fivefold repetition",
+            Self::FiftyMoveRule => "the fifty-move rule",
+            Self::SeventyFiveMoveRule => "the seventy-five-move rule",
+            Self::InsufficientMaterial => "insufficient material",
+            Self::TimeoutVsInsufficientMaterial => "timeout against insufficient material",
+        })
+    }
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        *self != Self::InProgress
+    }
+
+    /// The side that won, if the game ended decisively.
+    pub fn winner(&self) -> Option<PieceColor> {
+        match *self {
+            Self::Checkmate { winner }
+            | Self::Resigned { winner }
+            | Self::TimeForfeit { winner } => Some(winner),
+            Self::Recorded { winner } => winner,
+
+            _ => None,
+        }
+    }
+}
+
+/// Why [`Game::play`] refused a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    GameOver,
+    NoPiece,
+    NotYourTurn,
+    Illegal,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::GameOver => "the game is already over",
+            Self::NoPiece => "there is no piece on that square",
+            Self::NotYourTurn => "it is the other side's turn",
+            Self::Illegal => "that move is not legal",
+        })
+    }
+}
+
+impl Error for MoveError {}
+
+/// A game in progress: every position reached so far, the moves between
+/// them, and whether the game has ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    /// From the starting position up to and including the current one.
+    positions: Vec<Position>,
+    moves: Vec<Move>,
+    /// Moves taken back, the most recent last, for redoing.
+    undone: Vec<Move>,
+    status: GameStatus,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    /// A game from the standard starting position.
+    pub fn new() -> Self {
+        Self::from_position(Position::start())
+    }
+
+    pub fn from_position(position: Position) -> Self {
+        let mut game = Self {
+            positions: vec![position],
+            moves: vec![],
+            undone: vec![],
+            status: GameStatus::InProgress,
+        };
+
+        game.update_status();
+        game
+    }
+
+    pub fn position(&self) -> &Position {
+        self.positions.last().unwrap()
+    }
+
+    /// The position the game started from.
+    pub fn start_position(&self) -> &Position {
+        &self.positions[0]
+    }
+
+    /// Every position reached so far, from the start up to the current one.
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    /// The moves played so far, in order.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn side_to_move(&self) -> PieceColor {
+        self.position().side_to_move()
+    }
+
+    pub fn status(&self) -> GameStatus {
+        self.status
+    }
+
+    /// Plays `mv` for the side to move.
+    pub fn play(&mut self, mv: Move) -> Result<(), MoveError> {
+        if self.status.is_over() {
+            return Err(MoveError::GameOver);
+        }
+
+        let position = self.position();
+        let (_, color) = position.piece_at(mv.from).ok_or(MoveError::NoPiece)?;
+
+        if color != position.side_to_move() {
+            return Err(MoveError::NotYourTurn);
+        }
+
+        // Play the generated move rather than `mv`, so the flags are right
+        let mv = position
+            .legal_moves()
+            .into_iter()
+            .find(|&legal| legal == mv)
+            .ok_or(MoveError::Illegal)?;
+
+        // Replaying the move that was taken back keeps the rest to redo
+        if self.undone.last() == Some(&mv) {
+            self.undone.pop();
+        } else {
+            self.undone.clear();
+        }
+
+        self.push(mv);
+        Ok(())
+    }
+
+    /// Takes back the last move, returning it. Whatever ended the game,
+    /// even a resignation or a flag, is forgotten with it.
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.moves.pop()?;
+
+        self.positions.pop();
+        self.undone.push(mv);
+
+        self.status = GameStatus::InProgress;
+        self.update_status();
+
+        Some(mv)
+    }
+
+    /// Plays the last move taken back again, returning it, unless the game
+    /// has ended since.
+    pub fn redo(&mut self) -> Option<Move> {
+        if self.status.is_over() {
+            return None;
+        }
+
+        let mv = self.undone.pop()?;
+
+        self.push(mv);
+        Some(mv)
+    }
+
+    /// The moves taken back that [`Game::redo`] would replay, next first.
+    pub fn undone(&self) -> impl Iterator<Item = &Move> {
+        self.undone.iter().rev()
+    }
+
+    /// Undoes or redoes moves until `ply` moves have been played, or as
+    /// near as the redo history allows.
+    pub fn go_to(&mut self, ply: usize) {
+        while self.moves.len() > ply && self.undo().is_some() {}
+        while self.moves.len() < ply && self.redo().is_some() {}
+    }
+
+    fn push(&mut self, mv: Move) {
+        let mut next = self.position().clone();
+        next.make_move(mv);
+
+        self.positions.push(next);
+        self.moves.push(mv);
+        self.update_status();
+    }
+
+    /// How many times the current position has occurred, counting itself.
+    pub fn repetitions(&self) -> usize {
+        let current = self.position();
+
+        // Only positions since the last capture or pawn move can repeat it,
+        // and only those with the same side to move
+        self.positions
+            .iter()
+            .rev()
+            .take(current.halfmove_clock() as usize + 1)
+            .step_by(2)
+            .filter(|position| position.hash() == current.hash())
+            .count()
+    }
+
+    /// A draw the side to move may claim but that does not end the game on
+    /// its own.
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        if self.status.is_over() {
+            None
+        } else if self.repetitions() >= 3 {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.position().halfmove_clock() >= 100 {
+            Some(DrawReason::FiftyMoveRule)
+        } else {
+            None
+        }
+    }
+
+    /// Ends the game in a draw if one can be claimed, returning whether it
+    /// did.
+    pub fn claim_draw(&mut self) -> bool {
+        let Some(reason) = self.claimable_draw() else {
+            return false;
+        };
+
+        self.finish(GameStatus::Draw(reason));
+        true
+    }
+
+    pub fn resign(&mut self, color: PieceColor) {
+        self.finish(GameStatus::Resigned {
+            winner: color.opposite(),
+        });
+    }
+
+    pub fn agree_draw(&mut self) {
+        self.finish(GameStatus::Draw(DrawReason::Agreement));
+    }
+
+    /// Ends the game with a result decided elsewhere, a win for `winner`
+    /// or a draw with `None`, unless the moves have already ended it.
+    pub fn record_result(&mut self, winner: Option<PieceColor>) {
+        self.finish(GameStatus::Recorded { winner });
+    }
+
+    /// Ends the game because `color` ran out of time: a loss, unless the
+    /// opponent has nothing left to mate with.
+    pub fn flag(&mut self, color: PieceColor) {
+        let winner = color.opposite();
+
+        self.finish(if self.position().has_mating_material(winner) {
+            GameStatus::TimeForfeit { winner }
+        } else {
+            GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial)
+        });
+    }
+
+    fn finish(&mut self, status: GameStatus) {
+        if !self.status.is_over() {
+            self.status = status;
+        }
+    }
+
+    /// Ends the game if the current position decides it.
+    fn update_status(&mut self) {
+        let position = self.position();
+        let color = position.side_to_move();
+
+        // Mate on the move that reaches the seventy-fifth counts, so check it
+        // before the draws
+        let status = if position.legal_moves().is_empty() {
+            if position.is_in_check(color) {
+                GameStatus::Checkmate {
+                    winner: color.opposite(),
+                }
+            } else {
+                GameStatus::Stalemate
+            }
+        } else if position.is_insufficient_material() {
+            GameStatus::Draw(DrawReason::InsufficientMaterial)
+        } else if self.repetitions() >= 5 {
+            GameStatus::Draw(DrawReason::FivefoldRepetition)
+        } else if position.halfmove_clock() >= 150 {
+            GameStatus::Draw(DrawReason::SeventyFiveMoveRule)
+        } else {
+            return;
+        };
+
+        self.finish(status);
+    }
+}
@@ -0,0 +1,1241 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use chess::book::Selection;
+use chess::clock::Bonus;
+use chess::search::{Engine, Limits};
+use chess::{
+    parse_pgn, Book, Branch, Clock, Game, Move, PgnGame, Piece, PieceColor, Position, Square, Tags,
+    TimeControl,
+};
+use sfml::graphics::*;
+use sfml::window::{clipboard, Event, Key};
+
+use super::consts::*;
+
+pub struct Board<'a> {
+    game: Game,
+    /// The ply on show; anything short of the last one is a replay, and
+    /// the board takes no moves until it catches up.
+    ply: usize,
+    /// The games of the PGN being replayed, if `game` came from one.
+    library: Option<Library>,
+    square_drawing_shape: RectangleShape<'a>,
+    texture_drawing_shape: RectangleShape<'a>,
+    move_drawing_shape: CircleShape<'a>,
+    texture_rect: HashMap<(Piece, PieceColor), Rect<i32>>,
+    active_cell: Option<Square>,
+    moves: Vec<Move>,
+    active_piece_color: Option<PieceColor>,
+    /// A promotion waiting for the user to pick a piece.
+    pending_promotion: Option<Move>,
+    drag: Option<Drag>,
+    animation: Option<Animation>,
+    banner_shape: RectangleShape<'a>,
+    banner_text: Text<'a>,
+    /// Whether Black is at the bottom of the board.
+    flipped: bool,
+    /// Turns the board to whoever is to move, for players sharing a screen.
+    auto_flip: bool,
+    /// The side the computer plays, if any.
+    computer: Option<PieceColor>,
+    /// Lent to the search thread while the computer thinks.
+    engine: Option<Engine>,
+    thinking: Option<Thinking>,
+    book: Option<Book>,
+    book_selection: Selection,
+    panel_shape: RectangleShape<'a>,
+    panel_text: Text<'a>,
+    /// Restarts the clock with every new game.
+    time_control: Option<TimeControl>,
+    clock: Option<Clock>,
+    clock_text: Text<'a>,
+}
+
+/// Games read from PGN, to step through one at a time.
+struct Library {
+    games: Vec<PgnGame>,
+    /// The game on the board.
+    index: usize,
+    /// The variations taken to reach the line on the board, outermost
+    /// first.
+    path: Vec<Branch>,
+}
+
+/// A piece being dragged with the mouse.
+struct Drag {
+    from: Square,
+    /// The cursor, in window coordinates.
+    x: i32,
+    y: i32,
+    /// Whether the piece was already selected, so that dropping it back
+    /// where it was deselects it, as a second click would.
+    was_selected: bool,
+}
+
+/// A piece sliding across the board to the square it now stands on.
+struct Animation {
+    from: Square,
+    to: Square,
+    started: Instant,
+}
+
+/// A search running on a background thread, so the window keeps drawing.
+struct Thinking {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<(Engine, Option<Move>)>,
+}
+
+impl<'a> Board<'a> {
+    pub fn new(texture: &'a Texture, font: &'a Font, game: Game) -> Self {
+        Self {
+            banner_shape: {
+                let mut s = RectangleShape::new();
+                s.set_fill_color(Color::rgba(20, 20, 20, 200));
+                s
+            },
+            banner_text: {
+                let mut t = Text::new("", font, 36);
+                t.set_fill_color(Color::WHITE);
+                t
+            },
+            panel_shape: {
+                let mut s = RectangleShape::new();
+                s.set_size((PANEL_WIDTH as f32, HEIGHT as f32));
+                s.set_position((WIDTH as f32, 0.));
+                s.set_fill_color(Color::rgb(40, 40, 40));
+                s
+            },
+            panel_text: {
+                let mut t = Text::new("", font, 18);
+                t.set_fill_color(Color::rgb(230, 230, 230));
+                t
+            },
+            clock_text: {
+                let mut t = Text::new("", font, 48);
+                t.set_fill_color(Color::rgb(230, 230, 230));
+                t
+            },
+            time_control: None,
+            clock: None,
+            flipped: false,
+            auto_flip: false,
+            computer: None,
+            engine: Some(Engine::default()),
+            thinking: None,
+            book: None,
+            book_selection: Selection::default(),
+            active_piece_color: None,
+            moves: Vec::new(),
+            active_cell: None,
+            pending_promotion: None,
+            drag: None,
+            animation: None,
+            ply: game.moves().len(),
+            library: None,
+            game,
+            texture_rect: {
+                let mut map = HashMap::with_capacity(12);
+
+                const SUB_TXR_HEIGHT: i32 = IMAGE_HEIGHT / 2; // 2 cols
+                const SUB_TXR_WIDTH: i32 = IMAGE_WIDTH / 6; // 6 rows
+
+                for row in 0..2 {
+                    let color = [PieceColor::White, PieceColor::Black][row];
+                    for col in 0..6 {
+                        let piece = Piece::from_u8(col as _).unwrap();
+
+                        map.insert(
+                            (piece, color),
+                            Rect::new(
+                                col * SUB_TXR_WIDTH,
+                                row as i32 * SUB_TXR_HEIGHT,
+                                SUB_TXR_WIDTH,
+                                SUB_TXR_HEIGHT,
+                            ),
+                        );
+                    }
+                }
+
+                map
+            },
+
+            square_drawing_shape: {
+                let mut s = RectangleShape::new();
+                s.set_size((SQUARE_WIDTH as _, SQUARE_HEIGHT as _));
+                s.set_outline_thickness(0.);
+                s
+            },
+
+            texture_drawing_shape: {
+                let mut s = RectangleShape::new();
+                s.set_size((SQUARE_WIDTH as _, SQUARE_HEIGHT as _));
+                s.set_outline_thickness(0.);
+                s.set_texture(texture, true);
+                s
+            },
+
+            move_drawing_shape: {
+                let mut c = CircleShape::new(8., 20);
+                c.set_origin((
+                    -(SQUARE_WIDTH as f32 / 2. - 8.),
+                    -(SQUARE_HEIGHT as f32 / 2. - 8.),
+                ));
+                c.set_fill_color(Color::GREEN);
+                c
+            },
+        }
+    }
+
+    pub fn draw(&mut self, window: &mut RenderWindow) {
+        let bottom = self.bottom();
+
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                let square = to_square(row, col, bottom);
+                let is_white_square = (row + col) % 2 == 0;
+
+                self.square_drawing_shape
+                    .set_position(((col * SQUARE_WIDTH) as _, (row * SQUARE_HEIGHT) as _));
+
+                self.texture_drawing_shape
+                    .set_position(((col * SQUARE_WIDTH) as _, (row * SQUARE_HEIGHT) as _));
+
+                self.square_drawing_shape
+                    .set_fill_color(if is_white_square {
+                        square_color(PieceColor::White)
+                    } else {
+                        square_color(PieceColor::Black)
+                    });
+
+                if self.active_cell == Some(square) {
+                    let color = Color::rgb(50, 200, 50);
+                    self.square_drawing_shape.set_fill_color(color);
+                }
+
+                window.draw(&self.square_drawing_shape);
+
+                // Moving pieces are drawn on top of everything else
+                let moving = self.drag.as_ref().is_some_and(|drag| drag.from == square)
+                    || self
+                        .animation
+                        .as_ref()
+                        .is_some_and(|anim| anim.to == square);
+
+                if let Some(piece) = self.shown_position().piece_at(square) {
+                    if !moving {
+                        self.texture_drawing_shape
+                            .set_texture_rect(self.texture_rect[&piece]);
+
+                        window.draw(&self.texture_drawing_shape);
+                    }
+                }
+            }
+        }
+
+        // Draw possible moves (if a piece is selected)
+        for square in self.moves.iter().map(|mv| mv.to) {
+            self.move_drawing_shape.set_fill_color(Color::GREEN);
+
+            if let Some((_, piece_color)) = self.game.position().piece_at(square) {
+                if self
+                    .active_piece_color
+                    .is_some_and(|i| i.is_opposite(&piece_color))
+                {
+                    self.move_drawing_shape.set_fill_color(Color::RED);
+                }
+            }
+
+            let (row, col) = to_cell(square, bottom);
+            self.move_drawing_shape
+                .set_position(((col * SQUARE_WIDTH) as _, (row * SQUARE_HEIGHT) as _));
+
+            window.draw(&self.move_drawing_shape);
+        }
+
+        self.draw_moving_piece(window);
+
+        if let Some(mv) = self.pending_promotion {
+            self.draw_promotion_chooser(window, mv);
+        }
+
+        self.draw_panel(window);
+
+        let status = self.game.status();
+
+        if !self.is_live() {
+            let hint = format!("Move {} of {}", self.ply, self.game.moves().len());
+            self.draw_banner(window, &hint, 20, HEIGHT as f32 - 30.);
+        } else if status.is_over() {
+            self.draw_banner(window, &status.to_string(), 36, HEIGHT as f32 / 2.);
+        } else if let Some(reason) = self.game.claimable_draw() {
+            let hint = format!("Press D to claim a draw by {reason}");
+            self.draw_banner(window, &hint, 20, HEIGHT as f32 - 30.);
+        } else if self.thinking.is_some() {
+            self.draw_banner(window, "Thinking...", 20, HEIGHT as f32 - 30.);
+        }
+    }
+
+    /// Starts the computer thinking when it is its turn, and plays its move
+    /// once it has one. Call once per frame.
+    pub fn update(&mut self) {
+        if let Some(color) = self.clock.as_ref().and_then(Clock::flagged) {
+            self.cancel_thinking();
+            self.game.flag(color);
+        }
+
+        if self.game.status().is_over() {
+            if let Some(clock) = &mut self.clock {
+                clock.pause();
+            }
+        }
+
+        if let Some(thinking) = &self.thinking {
+            if !thinking.thread.is_finished() {
+                return;
+            }
+
+            if let Some(mv) = self.collect_engine() {
+                self.play(mv);
+            }
+        }
+
+        let computer_to_move = self.computer == Some(self.game.side_to_move());
+
+        if !computer_to_move || !self.is_live() || self.game.status().is_over() {
+            return;
+        }
+
+        let position = self.game.position().clone();
+
+        if let Some(book) = &self.book {
+            if let Some(mv) = book.choose(&position, self.book_selection) {
+                self.play(mv);
+                return;
+            }
+        }
+
+        let Some(mut engine) = self.engine.take() else {
+            return;
+        };
+
+        let history: Vec<u64> = self.game.positions()[..self.game.moves().len()]
+            .iter()
+            .map(Position::hash)
+            .collect();
+
+        let limits = match &self.clock {
+            Some(clock) => clock_limits(clock, position.side_to_move()),
+            None => Limits {
+                movetime: Some(ENGINE_MOVETIME),
+                ..Limits::default()
+            },
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+
+            thread::spawn(move || {
+                let best = engine.search(&position, &history, &limits, &stop, |_| {});
+                (engine, best)
+            })
+        };
+
+        self.thinking = Some(Thinking { stop, thread });
+    }
+
+    /// Uses `book` for the computer's opening moves and the side panel.
+    pub fn set_book(&mut self, book: Book) {
+        self.book = Some(book);
+    }
+
+    /// Plays the following games under `control`, or untimed with `None`.
+    /// The current game starts again with fresh clocks.
+    pub fn set_time_control(&mut self, control: Option<TimeControl>) {
+        self.time_control = control;
+        self.reset_clock();
+    }
+
+    fn reset_clock(&mut self) {
+        self.clock = self.time_control.clone().map(Clock::new);
+    }
+
+    /// Makes the computer play `color`, or neither side with `None`. The
+    /// board turns to put the human's pieces at the bottom.
+    pub fn set_computer(&mut self, color: Option<PieceColor>) {
+        self.cancel_thinking();
+        self.computer = color;
+        self.clear_selection();
+
+        if let Some(color) = color {
+            self.flipped = color == PieceColor::White;
+        }
+    }
+
+    /// Turns the board round.
+    pub fn flip(&mut self) {
+        self.flipped = !self.flipped;
+    }
+
+    /// Turns the board to the side to move after every move, or keeps it
+    /// as it is.
+    pub fn set_auto_flip(&mut self, auto_flip: bool) {
+        self.auto_flip = auto_flip;
+    }
+
+    /// The side whose pieces start at the bottom of the screen.
+    pub fn bottom(&self) -> PieceColor {
+        if self.auto_flip {
+            self.shown_position().side_to_move()
+        } else if self.flipped {
+            PieceColor::Black
+        } else {
+            PieceColor::White
+        }
+    }
+
+    /// Waits for the search thread and takes the engine back, returning
+    /// the move it found.
+    fn collect_engine(&mut self) -> Option<Move> {
+        let thinking = self.thinking.take()?;
+        let (engine, best) = thinking.thread.join().ok()?;
+
+        self.engine = Some(engine);
+        best
+    }
+
+    /// Stops the computer thinking and throws its move away, for when the
+    /// game it was thinking about changes under it.
+    fn cancel_thinking(&mut self) {
+        if let Some(thinking) = &self.thinking {
+            thinking.stop.store(true, Ordering::Relaxed);
+            self.collect_engine();
+        }
+    }
+
+    /// Draws the piece under the cursor, or the one sliding to its square.
+    fn draw_moving_piece(&mut self, window: &mut RenderWindow) {
+        let bottom = self.bottom();
+        let corner = |square: Square| {
+            let (row, col) = to_cell(square, bottom);
+            ((col * SQUARE_WIDTH) as f32, (row * SQUARE_HEIGHT) as f32)
+        };
+
+        let (square, position) = if let Some(drag) = &self.drag {
+            let centre = (
+                drag.x as f32 - SQUARE_WIDTH as f32 / 2.,
+                drag.y as f32 - SQUARE_HEIGHT as f32 / 2.,
+            );
+
+            (drag.from, centre)
+        } else if let Some(animation) = &self.animation {
+            let t = animation.started.elapsed().as_secs_f32() / ANIMATION_TIME.as_secs_f32();
+
+            if t >= 1. {
+                self.animation = None;
+                return;
+            }
+
+            // Ease out, so the piece settles into its square
+            let t = 1. - (1. - t) * (1. - t);
+            let (from, to) = (corner(animation.from), corner(animation.to));
+
+            (
+                animation.to,
+                (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t),
+            )
+        } else {
+            return;
+        };
+
+        let Some(piece) = self.shown_position().piece_at(square) else {
+            return;
+        };
+
+        self.texture_drawing_shape
+            .set_texture_rect(self.texture_rect[&piece]);
+        self.texture_drawing_shape.set_position(position);
+        window.draw(&self.texture_drawing_shape);
+    }
+
+    /// Draws the side panel: the book moves for the position on show.
+    fn draw_panel(&mut self, window: &mut RenderWindow) {
+        window.draw(&self.panel_shape);
+
+        let mode = match self.book_selection {
+            Selection::Best => "best move",
+            Selection::Weighted => "weighted",
+        };
+
+        let mut lines = vec![format!("Opening book ({mode})")];
+        let position = self.shown_position();
+
+        match &self.book {
+            None => lines.push("No book loaded".into()),
+
+            Some(book) => {
+                let moves = book.moves(position);
+                let total: u32 = moves.iter().map(|book| book.weight as u32).sum();
+
+                if moves.is_empty() {
+                    lines.push("Out of book".into());
+                }
+
+                for book_move in moves.iter().take(PANEL_BOOK_MOVES) {
+                    let share = book_move.weight as u32 * 100 / total.max(1);
+                    lines.push(format!("{:<8} {share:>3}%", book_move.mv.to_san(position)));
+                }
+            }
+        }
+
+        lines.push(String::new());
+        lines.push("M: switch book mode".into());
+
+        if let Some(library) = &self.library {
+            lines.push(String::new());
+            lines.push(format!(
+                "Game {} of {}",
+                library.index + 1,
+                library.games.len()
+            ));
+            lines.push("PgUp/PgDn: other games".into());
+
+            if self.next_branch().is_some() {
+                lines.push("Down: into the variation".into());
+            }
+
+            if !library.path.is_empty() {
+                lines.push(format!(
+                    "Up: out of variation ({} deep)",
+                    library.path.len()
+                ));
+            }
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            self.panel_text.set_string(line);
+            self.panel_text
+                .set_position((WIDTH as f32 + 16., 16. + i as f32 * 26.));
+            window.draw(&self.panel_text);
+        }
+
+        self.draw_clocks(window);
+    }
+
+    /// Draws the clocks at the foot of the side panel, the one for the
+    /// side at the bottom of the board lowest, with the running one lit.
+    fn draw_clocks(&mut self, window: &mut RenderWindow) {
+        let Some(clock) = &self.clock else {
+            return;
+        };
+
+        let bottom = self.bottom();
+        let clocks = [
+            (bottom.opposite(), HEIGHT as f32 - 160.),
+            (bottom, HEIGHT as f32 - 80.),
+        ];
+
+        for (color, y) in clocks {
+            let text = format!("{:?}  {}", color, format_clock(clock.remaining(color)));
+
+            self.clock_text
+                .set_fill_color(if clock.active() == Some(color) {
+                    Color::WHITE
+                } else {
+                    Color::rgb(130, 130, 130)
+                });
+
+            self.clock_text
+                .set_character_size(if clock.active() == Some(color) {
+                    40
+                } else {
+                    36
+                });
+
+            self.clock_text.set_string(&text);
+            self.clock_text.set_position((WIDTH as f32 + 16., y));
+            window.draw(&self.clock_text);
+        }
+    }
+
+    /// Draws `text` on a dark strip across the board, centred on `y`.
+    fn draw_banner(&mut self, window: &mut RenderWindow, text: &str, size: u32, y: f32) {
+        self.banner_text.set_string(text);
+        self.banner_text.set_character_size(size);
+
+        let bounds = self.banner_text.local_bounds();
+        self.banner_text.set_origin((
+            bounds.left + bounds.width / 2.,
+            bounds.top + bounds.height / 2.,
+        ));
+        self.banner_text.set_position((WIDTH as f32 / 2., y));
+
+        let height = size as f32 * 2.;
+        self.banner_shape.set_size((WIDTH as f32, height));
+        self.banner_shape.set_position((0., y - height / 2.));
+
+        window.draw(&self.banner_shape);
+        window.draw(&self.banner_text);
+    }
+
+    /// Draws the four promotion choices in a column running from the
+    /// promotion square towards the middle of the board.
+    fn draw_promotion_chooser(&mut self, window: &mut RenderWindow, mv: Move) {
+        let color = self.game.side_to_move();
+
+        for (piece, (row, col)) in promotion_cells(mv, self.bottom()) {
+            let position = ((col * SQUARE_WIDTH) as f32, (row * SQUARE_HEIGHT) as f32);
+
+            self.square_drawing_shape.set_position(position);
+            self.square_drawing_shape
+                .set_fill_color(Color::rgb(235, 235, 235));
+            self.square_drawing_shape
+                .set_outline_color(Color::rgb(50, 50, 50));
+            self.square_drawing_shape.set_outline_thickness(-2.);
+            window.draw(&self.square_drawing_shape);
+            self.square_drawing_shape.set_outline_thickness(0.);
+
+            self.texture_drawing_shape.set_position(position);
+            self.texture_drawing_shape
+                .set_texture_rect(self.texture_rect[&(piece, color)]);
+            window.draw(&self.texture_drawing_shape);
+        }
+    }
+
+    pub fn handle_event(&mut self, event: sfml::window::Event) {
+        match event {
+            Event::MouseButtonPressed { x, y, .. } => {
+                let Some(square) = self.get_square(x, y) else {
+                    return;
+                };
+
+                // Pressing on one of the mover's pieces picks it up; letting
+                // go on the same square leaves it selected for a click
+                if self.accepts_moves() && self.pending_promotion.is_none() {
+                    if let Some((_, color)) = self.game.position().piece_at(square) {
+                        if color == self.game.side_to_move() {
+                            let was_selected = self.active_cell == Some(square);
+
+                            self.select(square);
+                            self.drag = Some(Drag {
+                                from: square,
+                                x,
+                                y,
+                                was_selected,
+                            });
+
+                            return;
+                        }
+                    }
+                }
+
+                self.handle_click(square);
+            }
+
+            Event::MouseMoved { x, y } => {
+                if let Some(drag) = &mut self.drag {
+                    drag.x = x;
+                    drag.y = y;
+                }
+            }
+
+            Event::MouseButtonReleased { x, y, .. } => {
+                let Some(drag) = self.drag.take() else {
+                    return;
+                };
+
+                match self.get_square(x, y) {
+                    Some(square) if square == drag.from => {
+                        if drag.was_selected {
+                            self.clear_selection();
+                        }
+                    }
+
+                    // The piece is already where it was dropped, so it needs
+                    // no slide; an illegal drop snaps back
+                    Some(square) => {
+                        self.handle_click(square);
+                        self.animation = None;
+                    }
+
+                    None => self.clear_selection(),
+                }
+            }
+
+            Event::KeyPressed {
+                code: Key::Escape, ..
+            } => self.clear_selection(),
+
+            Event::KeyPressed {
+                code: Key::Left, ..
+            } => self.show_ply(self.ply.saturating_sub(1)),
+
+            Event::KeyPressed {
+                code: Key::Right, ..
+            } => self.show_ply(self.ply + 1),
+
+            Event::KeyPressed {
+                code: Key::Home, ..
+            } => self.show_ply(0),
+
+            Event::KeyPressed { code: Key::End, .. } => self.show_ply(usize::MAX),
+
+            Event::KeyPressed {
+                code: Key::PageUp, ..
+            } => self.show_game(|index| index.checked_sub(1)),
+
+            Event::KeyPressed {
+                code: Key::PageDown,
+                ..
+            } => self.show_game(|index| index.checked_add(1)),
+
+            Event::KeyPressed {
+                code: Key::Down, ..
+            } => self.enter_variation(),
+
+            Event::KeyPressed { code: Key::Up, .. } => self.leave_variation(),
+
+            Event::KeyPressed {
+                code: Key::Z,
+                ctrl: true,
+                shift: false,
+                ..
+            }
+            | Event::KeyPressed {
+                code: Key::Backspace,
+                ..
+            } => self.undo(),
+
+            Event::KeyPressed {
+                code: Key::Y,
+                ctrl: true,
+                ..
+            }
+            | Event::KeyPressed {
+                code: Key::Z,
+                ctrl: true,
+                shift: true,
+                ..
+            } => self.redo(),
+
+            // Carry on from the move on show, taking back the ones after it
+            Event::KeyPressed {
+                code: Key::Enter, ..
+            } => self.go_to(self.ply),
+
+            // Play against the computer from the current position
+            Event::KeyPressed { code: Key::W, .. } => self.set_computer(Some(PieceColor::Black)),
+            Event::KeyPressed { code: Key::B, .. } => self.set_computer(Some(PieceColor::White)),
+            Event::KeyPressed { code: Key::H, .. } => self.set_computer(None),
+
+            Event::KeyPressed { code: Key::F, .. } => self.flip(),
+            Event::KeyPressed { code: Key::A, .. } => self.set_auto_flip(!self.auto_flip),
+
+            Event::KeyPressed { code: Key::M, .. } => {
+                self.book_selection = match self.book_selection {
+                    Selection::Best => Selection::Weighted,
+                    Selection::Weighted => Selection::Best,
+                };
+            }
+
+            Event::KeyPressed { code: Key::D, .. } => {
+                self.game.claim_draw();
+            }
+
+            // Load a position from a FEN string on the clipboard, or a game
+            // to replay from PGN
+            Event::KeyPressed {
+                code: Key::V,
+                ctrl: true,
+                ..
+            } => {
+                let text = clipboard::get_string();
+
+                if let Ok(position) = Position::from_fen(text.trim()) {
+                    self.set_game(Game::from_position(position));
+                    return;
+                }
+
+                match parse_pgn(&text) {
+                    Ok(games) if !games.is_empty() => self.set_games(games),
+
+                    Ok(_) => eprintln!("No FEN or PGN on the clipboard"),
+                    Err(e) => eprintln!("Invalid PGN on the clipboard: {e}"),
+                }
+            }
+
+            // Copy the game so far to the clipboard as PGN
+            Event::KeyPressed {
+                code: Key::C,
+                ctrl: true,
+                ..
+            } => clipboard::set_string(&self.pgn()),
+
+            Event::KeyPressed {
+                code: Key::S,
+                ctrl: true,
+                ..
+            } => match fs::write(PGN_FILE, self.pgn()) {
+                Ok(()) => println!("Saved the game to {PGN_FILE}"),
+                Err(e) => eprintln!("Could not save the game to {PGN_FILE}: {e}"),
+            },
+
+            _ => {}
+        }
+    }
+
+    /// The game as PGN, dated today.
+    fn pgn(&self) -> String {
+        let tags = Tags {
+            event: "Casual game".into(),
+            date: today(),
+            ..Tags::default()
+        };
+
+        self.game.to_pgn(&tags)
+    }
+
+    /// Puts `game` on the board, leaving any PGN being replayed.
+    pub fn set_game(&mut self, game: Game) {
+        self.library = None;
+        self.load(game);
+    }
+
+    /// Replays `games`, from the start of the first. Does nothing if there
+    /// are none.
+    pub fn set_games(&mut self, games: Vec<PgnGame>) {
+        let Some(game) = games.first().map(PgnGame::to_game) else {
+            return;
+        };
+
+        self.library = Some(Library {
+            games,
+            index: 0,
+            path: vec![],
+        });
+
+        self.load(game);
+        self.show_ply(0);
+    }
+
+    /// Puts another game of the library on the board, from its start: the
+    /// one at the index `step` gives for the current one, if there is one.
+    fn show_game(&mut self, step: impl FnOnce(usize) -> Option<usize>) {
+        let Some(library) = &mut self.library else {
+            return;
+        };
+
+        let Some(index) = step(library.index).filter(|&i| i < library.games.len()) else {
+            return;
+        };
+
+        library.index = index;
+        library.path.clear();
+
+        let game = library.games[index].to_game();
+        self.load(game);
+        self.show_ply(0);
+    }
+
+    /// The branch off the line on the board that Down takes from the ply on
+    /// show: the next variation along if one was just entered here, else
+    /// the first variation of the move about to be played. `None` once the
+    /// board has left the file's moves.
+    fn next_branch(&self) -> Option<Vec<Branch>> {
+        let library = self.library.as_ref()?;
+        let game = &library.games[library.index];
+        let line = game.line(&library.path)?;
+
+        // Moves played on the board since take it off the file
+        let played = self.game.moves();
+
+        if played.len() > line.len() || played.iter().zip(&line).any(|(&mv, pgn)| mv != pgn.mv) {
+            return None;
+        }
+
+        let mut paths = vec![];
+
+        if let Some((&last, outer)) = library
+            .path
+            .split_last()
+            .filter(|(branch, _)| branch.ply == self.ply)
+        {
+            let next = Branch {
+                index: last.index + 1,
+                ..last
+            };
+
+            paths.push([outer, &[next]].concat());
+        }
+
+        let first = Branch {
+            ply: self.ply,
+            index: 0,
+        };
+
+        paths.push([&library.path[..], &[first]].concat());
+        paths.into_iter().find(|path| game.line(path).is_some())
+    }
+
+    /// Follows [`Board::next_branch`], showing the position the variation
+    /// starts from.
+    fn enter_variation(&mut self) {
+        let Some(path) = self.next_branch() else {
+            return;
+        };
+
+        let ply = self.ply;
+        let library = self.library.as_mut().expect("a branch needs a library");
+
+        let Some(game) = library.games[library.index].variation(&path) else {
+            return;
+        };
+
+        library.path = path;
+        self.load(game);
+        self.show_ply(ply);
+    }
+
+    /// Goes back to the line the variation on the board branched off,
+    /// showing the position it branched from.
+    fn leave_variation(&mut self) {
+        let Some(library) = &mut self.library else {
+            return;
+        };
+
+        let Some(branch) = library.path.pop() else {
+            return;
+        };
+
+        if let Some(game) = library.games[library.index].variation(&library.path) {
+            self.load(game);
+            self.show_ply(branch.ply);
+        }
+    }
+
+    /// Puts `game` on the board, at its last move, with fresh clocks.
+    fn load(&mut self, game: Game) {
+        self.cancel_thinking();
+        self.ply = game.moves().len();
+        self.game = game;
+        self.animation = None;
+        self.clear_selection();
+        self.reset_clock();
+    }
+
+    /// Shows the position after `ply` plies, clamped to the game so far.
+    pub fn show_ply(&mut self, ply: usize) {
+        self.cancel_thinking();
+
+        let from = self.ply;
+        self.ply = ply.min(self.game.moves().len());
+        self.clear_selection();
+
+        // Stepping one move either way slides the piece that moved
+        let moves = self.game.moves();
+
+        if self.ply == from + 1 {
+            let mv = moves[from];
+            self.slide(mv.from, mv.to);
+        } else if self.ply + 1 == from {
+            let mv = moves[self.ply];
+            self.slide(mv.to, mv.from);
+        }
+    }
+
+    /// Takes back the last move, and the computer's reply before it, so
+    /// that it is a human's turn again.
+    pub fn undo(&mut self) {
+        if self.flag_fell() {
+            return;
+        }
+
+        self.cancel_thinking();
+
+        if self.game.undo().is_some() && self.computer == Some(self.game.side_to_move()) {
+            self.game.undo();
+        }
+
+        self.after_history_change();
+
+        let undone = self.game.undone().next().copied();
+
+        if let Some(mv) = undone {
+            self.slide(mv.to, mv.from);
+        }
+    }
+
+    /// Plays the last move taken back again, and the computer's reply
+    /// after it.
+    pub fn redo(&mut self) {
+        if self.flag_fell() {
+            return;
+        }
+
+        self.cancel_thinking();
+
+        if self.game.redo().is_some() && self.computer == Some(self.game.side_to_move()) {
+            self.game.redo();
+        }
+
+        self.after_history_change();
+
+        if let Some(&mv) = self.game.moves().last() {
+            self.slide(mv.from, mv.to);
+        }
+    }
+
+    /// Takes back or replays moves until `ply` have been played.
+    pub fn go_to(&mut self, ply: usize) {
+        if self.flag_fell() {
+            return;
+        }
+
+        self.cancel_thinking();
+        self.game.go_to(ply);
+        self.after_history_change();
+    }
+
+    /// Shows the new end of the game, and hands the clock, if it is
+    /// running, to whoever is to move there.
+    fn after_history_change(&mut self) {
+        self.ply = self.game.moves().len();
+        self.clear_selection();
+
+        if let Some(clock) = &mut self.clock {
+            if clock.active().is_some() && !self.game.status().is_over() {
+                clock.start(self.game.side_to_move());
+            }
+        }
+    }
+
+    /// Whether a player has run out of time. That is final: the clock
+    /// cannot give the time back, so the moves before it stay played.
+    fn flag_fell(&self) -> bool {
+        self.clock.as_ref().and_then(Clock::flagged).is_some()
+    }
+
+    fn shown_position(&self) -> &Position {
+        &self.game.positions()[self.ply]
+    }
+
+    /// Whether the latest position is on show.
+    fn is_live(&self) -> bool {
+        self.ply == self.game.moves().len()
+    }
+
+    /// Whether the user may move now: the game is on, on show, and it is
+    /// not the computer's turn.
+    fn accepts_moves(&self) -> bool {
+        let computer_to_move = self.computer == Some(self.game.side_to_move());
+
+        !self.game.status().is_over() && self.is_live() && !computer_to_move
+    }
+
+    fn select(&mut self, square: Square) {
+        self.active_cell = Some(square);
+        self.active_piece_color = self.game.position().piece_at(square).map(|(_, c)| c);
+        self.pending_promotion = None;
+        self.moves = self
+            .game
+            .position()
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| mv.from == square)
+            .collect();
+    }
+
+    fn handle_click(&mut self, square: Square) {
+        if !self.accepts_moves() {
+            return;
+        }
+
+        if let Some(mv) = self.pending_promotion.take() {
+            // Clicking anywhere but one of the choices cancels the move
+            let bottom = self.bottom();
+
+            if let Some((piece, _)) =
+                promotion_cells(mv, bottom).find(|(_, cell)| *cell == to_cell(square, bottom))
+            {
+                self.play(Move::with_promotion(mv.from, mv.to, piece));
+            }
+
+            self.clear_selection();
+            return;
+        }
+
+        let clicked = self.game.position().piece_at(square);
+
+        match (self.active_cell, clicked) {
+            // Only the side to move may pick up a piece
+            (None, Some((_, color))) if color == self.game.side_to_move() => self.select(square),
+
+            (Some(_), _) => {
+                match self.moves.iter().find(|mv| mv.to == square) {
+                    // Wait for the user to pick a piece before playing it
+                    Some(mv) if mv.promotion.is_some() => {
+                        self.pending_promotion = Some(Move::new(mv.from, mv.to));
+                        self.moves.clear();
+                        return;
+                    }
+
+                    Some(&mv) => self.play(mv),
+
+                    None => {}
+                }
+
+                self.clear_selection();
+            }
+
+            _ => {}
+        }
+    }
+
+    fn play(&mut self, mv: Move) {
+        // Only moves for the side to move are ever offered, so this cannot
+        // be refused
+        let _ = self.game.play(mv);
+        self.ply = self.game.moves().len();
+        self.slide(mv.from, mv.to);
+
+        // The first move starts the clock for the reply
+        if let Some(clock) = &mut self.clock {
+            if clock.active().is_some() {
+                clock.press();
+            } else {
+                clock.start(self.game.side_to_move());
+            }
+        }
+    }
+
+    /// Slides the piece now on `to` over from `from`.
+    fn slide(&mut self, from: Square, to: Square) {
+        self.animation = Some(Animation {
+            from,
+            to,
+            started: Instant::now(),
+        });
+    }
+
+    fn clear_selection(&mut self) {
+        self.drag = None;
+        self.active_cell = None;
+        self.active_piece_color = None;
+        self.pending_promotion = None;
+        self.moves.clear();
+    }
+
+    fn get_square(&self, x: i32, y: i32) -> Option<Square> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        // column of the cell
+        let col_idx = x as u32 / SQUARE_WIDTH;
+
+        // row of the cell
+        let row_idx = y as u32 / SQUARE_HEIGHT;
+
+        (row_idx < ROWS && col_idx < COLS).then(|| to_square(row_idx, col_idx, self.bottom()))
+    }
+}
+
+fn square_color(color: PieceColor) -> Color {
+    match color {
+        PieceColor::White => Color::rgb(170, 150, 150),
+        PieceColor::Black => Color::rgb(90, 70, 70),
+    }
+}
+
+/// Search limits for `color` to play on `clock`.
+fn clock_limits(clock: &Clock, color: PieceColor) -> Limits {
+    let mut limits = Limits {
+        moves_to_go: clock.moves_to_go(color),
+        overhead: ENGINE_OVERHEAD,
+        ..Limits::default()
+    };
+
+    // Either delay is worth at most its length back on every move, much
+    // like an increment
+    limits.time[color as usize] = Some(clock.remaining(color));
+    limits.increment[color as usize] = match clock.stage(color).bonus {
+        Bonus::Increment(time) | Bonus::Bronstein(time) | Bonus::Delay(time) => time,
+        Bonus::None => Duration::ZERO,
+    };
+
+    limits
+}
+
+/// `h:mm:ss`, `m:ss`, or `s.t` in the last ten seconds.
+fn format_clock(time: Duration) -> String {
+    let secs = time.as_secs();
+
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else if secs >= 10 {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    } else {
+        format!("{}.{}", secs, time.subsec_millis() / 100)
+    }
+}
+
+/// Today's UTC date in the PGN `YYYY.MM.DD` form.
+fn today() -> String {
+    let Ok(elapsed) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return Tags::default().date;
+    };
+
+    // Days since the epoch to a civil date, after Howard Hinnant's
+    // `civil_from_days`
+    let days = (elapsed.as_secs() / 86_400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+/// Screen row/column (row 0 at the top) to a board square, with `bottom`'s
+/// pieces starting at the bottom of the screen.
+fn to_square(row: u32, col: u32, bottom: PieceColor) -> Square {
+    match bottom {
+        PieceColor::White => Square::new(col as u8, (ROWS - 1 - row) as u8),
+        PieceColor::Black => Square::new((COLS - 1 - col) as u8, row as u8),
+    }
+}
+
+fn to_cell(square: Square, bottom: PieceColor) -> (u32, u32) {
+    let (rank, file) = (square.rank() as u32, square.file() as u32);
+
+    match bottom {
+        PieceColor::White => (ROWS - 1 - rank, file),
+        PieceColor::Black => (rank, COLS - 1 - file),
+    }
+}
+
+/// The screen cells of the promotion chooser for `mv`, paired with the piece
+/// each one selects.
+fn promotion_cells(mv: Move, bottom: PieceColor) -> impl Iterator<Item = (Piece, (u32, u32))> {
+    let (row, col) = to_cell(mv.to, bottom);
+
+    Piece::PROMOTIONS
+        .into_iter()
+        .enumerate()
+        .map(move |(i, piece)| {
+            let i = i as u32;
+            let row = if row == 0 { row + i } else { row - i };
+
+            (piece, (row, col))
+        })
+}
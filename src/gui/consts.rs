@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+pub use chess::consts::*;
+
+pub const WIDTH: u32 = 800;
+pub const HEIGHT: u32 = 800;
+
+/// The side panel to the right of the board.
+pub const PANEL_WIDTH: u32 = 280;
+
+/// How many book moves the side panel lists.
+pub const PANEL_BOOK_MOVES: usize = 12;
+
+pub const SQUARE_WIDTH: u32 = WIDTH / ROWS;
+pub const SQUARE_HEIGHT: u32 = HEIGHT / COLS;
+
+pub const IMAGE_WIDTH: i32 = 2000;
+pub const IMAGE_HEIGHT: i32 = 668;
+pub const TEXTURE_DATA: &[u8; 89806] = include_bytes!("../../assets/Pieces.png");
+pub const FONT_DATA: &[u8] = include_bytes!("../../assets/DejaVuSans-Bold.ttf");
+
+/// How long a piece takes to slide to its square.
+pub const ANIMATION_TIME: Duration = Duration::from_millis(150);
+
+/// How long the computer thinks about each move when there is no clock.
+pub const ENGINE_MOVETIME: Duration = Duration::from_secs(1);
+
+/// Time the computer keeps back on a clock, for the frames it takes to
+/// notice its search has finished.
+pub const ENGINE_OVERHEAD: Duration = Duration::from_millis(50);
+
+/// Where Ctrl+S saves the current game.
+pub const PGN_FILE: &str = "game.pgn";
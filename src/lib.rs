@@ -0,0 +1,36 @@
+//! Rules engine for the chess front-end.
+//!
+//! Nothing in here depends on SFML, so the crate can be used on headless
+//! machines (servers, bots, tests) with `--no-default-features`.
+
+pub mod attacks;
+pub mod bitboard;
+pub mod book;
+pub mod clock;
+pub mod consts;
+pub mod eval;
+pub mod fen;
+pub mod game;
+pub mod movegen;
+pub mod moves;
+pub mod notation;
+pub mod perft;
+pub mod pgn;
+pub mod piece;
+pub mod position;
+pub mod search;
+pub mod square;
+mod zobrist;
+
+pub use bitboard::Bitboard;
+pub use book::Book;
+pub use clock::{Clock, TimeControl};
+pub use eval::{evaluate, Evaluation};
+pub use fen::FenError;
+pub use game::{DrawReason, Game, GameStatus, MoveError};
+pub use moves::{Move, MoveFlags, Undo};
+pub use notation::ParseMoveError;
+pub use pgn::{parse_pgn, Branch, PgnError, PgnErrorKind, PgnGame, PgnMove, Tags};
+pub use piece::{Piece, PieceColor};
+pub use position::{CastlingRights, Position};
+pub use square::Square;
@@ -1,19 +1,111 @@
-use sfml::{graphics::*, window::*};
+use std::process::ExitCode;
 
-mod board;
-mod consts;
-mod piece;
+mod cli;
+#[cfg(feature = "gui")]
+mod gui;
+mod uci;
 
-use consts::*;
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Some(code) = cli::run(&args) {
+        return code;
+    }
+
+    match parse_options(&args) {
+        Some(options) => run_window(options),
+        None => {
+            eprintln!("{}", cli::usage());
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// What to open the window with.
+#[derive(Default)]
+struct Options<'a> {
+    pgn: Option<&'a str>,
+    book: Option<&'a str>,
+    clock: Option<&'a str>,
+}
+
+/// `[--book <book.bin>] [--clock <control>] [game.pgn]`, in any order.
+fn parse_options(args: &[String]) -> Option<Options<'_>> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--book" => options.book = Some(args.next()?),
+            "--clock" => options.clock = Some(args.next()?),
+            path if path.ends_with(".pgn") && options.pgn.is_none() => options.pgn = Some(path),
+
+            _ => return None,
+        }
+    }
+
+    Some(options)
+}
+
+/// Reads every game of the PGN file at `path`, failing if there are none.
+#[cfg(feature = "gui")]
+fn load_pgn(path: &str) -> Result<Vec<chess::PgnGame>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let games = chess::parse_pgn(&text).map_err(|e| e.to_string())?;
+
+    if games.is_empty() {
+        return Err("no games in the file".into());
+    }
+
+    Ok(games)
+}
+
+/// Opens the board, replaying the games in the PGN file, consulting the
+/// opening book and playing on the clock given in `options`.
+#[cfg(feature = "gui")]
+fn run_window(options: Options) -> ExitCode {
+    use chess::{Book, Game, TimeControl};
+    use gui::consts::*;
+    use sfml::{graphics::*, window::*};
 
-fn main() {
     let window_setttings = ContextSettings {
         antialiasing_level: 4,
         ..Default::default()
     };
 
-    let mut window =
-        RenderWindow::new((WIDTH, HEIGHT), "Chess", Style::CLOSE, &window_setttings).unwrap();
+    let Options { pgn, book, clock } = options;
+
+    let replay = match pgn.map(load_pgn).transpose() {
+        Ok(games) => games,
+        Err(e) => {
+            eprintln!("Could not read {}: {e}", pgn.unwrap());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let book = match book.map(Book::open).transpose() {
+        Ok(book) => book,
+        Err(e) => {
+            eprintln!("Could not read {}: {e}", book.unwrap());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let clock = match clock.map(str::parse::<TimeControl>).transpose() {
+        Ok(clock) => clock,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut window = RenderWindow::new(
+        (WIDTH + PANEL_WIDTH, HEIGHT),
+        "Chess",
+        Style::CLOSE,
+        &window_setttings,
+    )
+    .unwrap();
 
     window.set_vertical_sync_enabled(true);
 
@@ -21,8 +113,19 @@ fn main() {
     t.load_from_memory(TEXTURE_DATA, Rect::new(0, 0, IMAGE_WIDTH, IMAGE_HEIGHT))
         .unwrap();
 
-    let mut board = board::Board::new(&t);
-    board.parse_fen(START_FEN);
+    let font = Font::from_memory_static(FONT_DATA).unwrap();
+
+    let mut board = gui::board::Board::new(&t, &font, Game::new());
+
+    if let Some(book) = book {
+        board.set_book(book);
+    }
+
+    board.set_time_control(clock);
+
+    if let Some(games) = replay {
+        board.set_games(games);
+    }
 
     while window.is_open() {
         while let Some(event) = window.poll_event() {
@@ -35,8 +138,19 @@ fn main() {
             }
         }
 
+        board.update();
+
         window.clear(Color::WHITE);
         board.draw(&mut window);
         window.display();
     }
+
+    ExitCode::SUCCESS
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_window(_options: Options) -> ExitCode {
+    eprintln!("This build has no window; rebuild with the `gui` feature to play.");
+    eprintln!("{}", cli::usage());
+    ExitCode::from(2)
 }
@@ -0,0 +1,211 @@
+//! Legal move generation and attack detection.
+
+use crate::attacks::*;
+use crate::bitboard::Bitboard;
+use crate::moves::{Move, MoveFlags};
+use crate::piece::*;
+use crate::position::Position;
+use crate::square::Square;
+
+impl Position {
+    /// Every legal move for the side to move.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let color = self.side_to_move();
+        let mut moves = Vec::with_capacity(64);
+
+        for from in self.color(color) {
+            self.piece_moves(from, &mut moves);
+        }
+
+        // Try each move and drop the ones that leave the king attacked
+        let mut scratch = self.clone();
+        moves.retain(|&mv| {
+            let undo = scratch.make_move(mv);
+            let legal = !scratch.is_in_check(color);
+            scratch.unmake_move(mv, undo);
+
+            legal
+        });
+
+        moves
+    }
+
+    /// Pseudo-legal moves for the piece on `square`: they follow the way the
+    /// piece moves but may leave its own king in check.
+    pub fn calc_moves(&self, square: Square) -> Vec<Move> {
+        let mut moves = vec![];
+        self.piece_moves(square, &mut moves);
+        moves
+    }
+
+    fn piece_moves(&self, from: Square, moves: &mut Vec<Move>) {
+        let Some((piece, color)) = self.piece_at(from) else {
+            return;
+        };
+
+        if piece == Piece::Pawn {
+            self.pawn_moves(from, color, moves);
+            return;
+        }
+
+        let enemy = self.color(color.opposite());
+        let targets = piece_attacks(piece, from, self.occupied()) & !self.color(color);
+
+        for to in targets {
+            let mv = Move::new(from, to);
+
+            moves.push(if enemy.contains(to) {
+                mv.with_flags(MoveFlags::CAPTURE)
+            } else {
+                mv
+            });
+        }
+
+        if piece == Piece::King {
+            moves.extend(
+                self.castling_moves(from)
+                    .into_iter()
+                    .map(|to| Move::new(from, to).with_flags(MoveFlags::CASTLE)),
+            );
+        }
+    }
+
+    /// Castling destinations for the king on `king`: the right must still be
+    /// held, the squares up to the rook must be empty, and the king may not
+    /// castle out of, through or into check.
+    pub(crate) fn castling_moves(&self, king: Square) -> Vec<Square> {
+        let mut moves = vec![];
+
+        let Some((Piece::King, color)) = self.piece_at(king) else {
+            return moves;
+        };
+
+        let rank = match color {
+            PieceColor::White => 0,
+            PieceColor::Black => 7,
+        };
+
+        if king != Square::new(4, rank) || self.is_in_check(color) {
+            return moves;
+        }
+
+        let rights = self.castling_rights();
+        let enemy = color.opposite();
+        let rooks = self.pieces(Piece::Rook, color);
+
+        let empty = |files: &[u8]| {
+            files
+                .iter()
+                .all(|&f| !self.occupied().contains(Square::new(f, rank)))
+        };
+
+        let safe = |files: &[u8]| {
+            files
+                .iter()
+                .all(|&f| !self.square_attacked_by(Square::new(f, rank), enemy))
+        };
+
+        if rights.kingside(color)
+            && rooks.contains(Square::new(7, rank))
+            && empty(&[5, 6])
+            && safe(&[5, 6])
+        {
+            moves.push(Square::new(6, rank));
+        }
+
+        if rights.queenside(color)
+            && rooks.contains(Square::new(0, rank))
+            && empty(&[1, 2, 3])
+            && safe(&[2, 3])
+        {
+            moves.push(Square::new(2, rank));
+        }
+
+        moves
+    }
+
+    /// Pushes every pseudo-legal move of a `color` pawn on `from`. Both
+    /// colors share this, only the direction and the ranks differ.
+    pub(crate) fn pawn_moves(&self, from: Square, color: PieceColor, moves: &mut Vec<Move>) {
+        let (forward, start_rank, last_rank) = match color {
+            PieceColor::White => (1, 1, 7),
+            PieceColor::Black => (-1, 6, 0),
+        };
+
+        let mut push = |to: Square, flags: MoveFlags| {
+            if to.rank() == last_rank {
+                moves.extend(
+                    Piece::PROMOTIONS
+                        .iter()
+                        .map(|&piece| Move::with_promotion(from, to, piece).with_flags(flags)),
+                );
+            } else {
+                moves.push(Move::new(from, to).with_flags(flags));
+            }
+        };
+
+        if let Some(one) = from
+            .offset(0, forward)
+            .filter(|&sq| self.piece_at(sq).is_none())
+        {
+            push(one, MoveFlags::NONE);
+
+            if from.rank() == start_rank {
+                if let Some(two) = from
+                    .offset(0, 2 * forward)
+                    .filter(|&sq| self.piece_at(sq).is_none())
+                {
+                    push(two, MoveFlags::DOUBLE_PUSH);
+                }
+            }
+        }
+
+        for df in [-1, 1] {
+            let Some(to) = from.offset(df, forward) else {
+                continue;
+            };
+
+            match self.piece_at(to) {
+                Some((_, c)) if c.is_opposite(&color) => push(to, MoveFlags::CAPTURE),
+
+                // Only the side to move can take en passant
+                None if self.en_passant() == Some(to) && color == self.side_to_move() => {
+                    push(to, MoveFlags::CAPTURE | MoveFlags::EN_PASSANT)
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    pub fn king_square(&self, color: PieceColor) -> Option<Square> {
+        self.pieces(Piece::King, color).first()
+    }
+
+    /// Whether `color`'s king is attacked. A side without a king is never in
+    /// check.
+    pub fn is_in_check(&self, color: PieceColor) -> bool {
+        self.king_square(color)
+            .is_some_and(|king| self.square_attacked_by(king, color.opposite()))
+    }
+
+    /// Whether any piece of `color` attacks `square`, regardless of what
+    /// stands on it.
+    pub fn square_attacked_by(&self, square: Square, color: PieceColor) -> bool {
+        !self.attackers(square, color).is_empty()
+    }
+
+    /// Every piece of `color` that attacks `square`.
+    pub fn attackers(&self, square: Square, color: PieceColor) -> Bitboard {
+        let occupied = self.occupied();
+        let queens = self.pieces(Piece::Queen, color);
+
+        // A pawn attacks `square` exactly when a pawn of the other color on
+        // `square` would attack it back
+        (pawn_attacks(color.opposite(), square) & self.pieces(Piece::Pawn, color))
+            | (knight_attacks(square) & self.pieces(Piece::Knight, color))
+            | (king_attacks(square) & self.pieces(Piece::King, color))
+            | (bishop_attacks(square, occupied) & (self.pieces(Piece::Bishop, color) | queens))
+            | (rook_attacks(square, occupied) & (self.pieces(Piece::Rook, color) | queens))
+    }
+}
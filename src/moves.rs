@@ -0,0 +1,123 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::piece::{Piece, PieceColor};
+use crate::position::CastlingRights;
+use crate::square::Square;
+
+/// Extra facts about a move, filled in by move generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MoveFlags(u8);
+
+impl MoveFlags {
+    pub const NONE: Self = Self(0);
+    pub const CAPTURE: Self = Self(1);
+    pub const CASTLE: Self = Self(1 << 1);
+    pub const EN_PASSANT: Self = Self(1 << 2);
+    pub const DOUBLE_PUSH: Self = Self(1 << 3);
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for MoveFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// A move of the piece on `from` to `to`.
+///
+/// Two moves are equal when their squares and promotion match; the flags
+/// follow from the position the move is played in, so a hand-built
+/// `Move::new` compares equal to the generated move it describes.
+#[derive(Debug, Clone, Copy, Eq)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    /// What a pawn reaching the last rank turns into.
+    pub promotion: Option<Piece>,
+    pub flags: MoveFlags,
+}
+
+impl PartialEq for Move {
+    fn eq(&self, other: &Self) -> bool {
+        self.from == other.from && self.to == other.to && self.promotion == other.promotion
+    }
+}
+
+impl Hash for Move {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.from.hash(state);
+        self.to.hash(state);
+        self.promotion.hash(state);
+    }
+}
+
+impl Move {
+    pub fn new(from: Square, to: Square) -> Self {
+        Self {
+            from,
+            to,
+            promotion: None,
+            flags: MoveFlags::NONE,
+        }
+    }
+
+    pub fn with_promotion(from: Square, to: Square, piece: Piece) -> Self {
+        Self {
+            promotion: Some(piece),
+            ..Self::new(from, to)
+        }
+    }
+
+    pub fn with_flags(self, flags: MoveFlags) -> Self {
+        Self { flags, ..self }
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.flags.contains(MoveFlags::CAPTURE)
+    }
+
+    pub fn is_castle(&self) -> bool {
+        self.flags.contains(MoveFlags::CASTLE)
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags.contains(MoveFlags::EN_PASSANT)
+    }
+
+    pub fn is_double_push(&self) -> bool {
+        self.flags.contains(MoveFlags::DOUBLE_PUSH)
+    }
+}
+
+/// Long algebraic notation, like `e2e4` or `e7e8q`.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+
+        if let Some(piece) = self.promotion {
+            write!(f, "{}", piece.to_char(PieceColor::Black))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Everything [`Position::make_move`](crate::Position::make_move) throws away,
+/// so that [`Position::unmake_move`](crate::Position::unmake_move) can put it
+/// back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    /// The captured piece and the square it stood on, which differs from
+    /// the move's `to` for en passant.
+    pub captured: Option<(Piece, PieceColor, Square)>,
+    pub castling: CastlingRights,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
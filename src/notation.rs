@@ -0,0 +1,239 @@
+//! Move notation: Standard Algebraic Notation for people and PGN, and the
+//! long algebraic notation of the UCI protocol.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::moves::Move;
+use crate::piece::*;
+use crate::position::Position;
+use crate::square::Square;
+
+/// Why [`Position::parse_san`] or [`Position::parse_uci`] rejected a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMoveError {
+    /// The text is not a move in the expected notation at all.
+    Invalid,
+    /// No legal move matches.
+    Illegal,
+    /// More than one legal move matches. Long algebraic notation is never
+    /// ambiguous.
+    Ambiguous,
+}
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Invalid => "not a move in that notation",
+            Self::Illegal => "no legal move matches",
+            Self::Ambiguous => "more than one legal move matches",
+        })
+    }
+}
+
+impl Error for ParseMoveError {}
+
+impl Move {
+    /// Formats the move in the long algebraic notation of the UCI protocol,
+    /// like `e2e4`, `e1g1` for castling or `e7e8q`.
+    pub fn to_uci(&self) -> String {
+        self.to_string()
+    }
+
+    /// Formats the move in Standard Algebraic Notation, like `Nbd7`, `exd5`,
+    /// `e8=Q+` or `O-O#`. `position` is the position the move is played
+    /// from, and the move must be legal in it.
+    pub fn to_san(&self, position: &Position) -> String {
+        let (piece, _) = position
+            .piece_at(self.from)
+            .expect("no piece on the from square");
+
+        let mut san = String::with_capacity(8);
+        let is_capture = position.piece_at(self.to).is_some()
+            || (piece == Piece::Pawn && self.from.file() != self.to.file());
+
+        if piece == Piece::King && self.from.file().abs_diff(self.to.file()) == 2 {
+            san.push_str(if self.to.file() == 6 { "O-O" } else { "O-O-O" });
+        } else if piece == Piece::Pawn {
+            if is_capture {
+                san.push((b'a' + self.from.file()) as char);
+                san.push('x');
+            }
+
+            san.push_str(&self.to.to_string());
+
+            if let Some(promotion) = self.promotion {
+                san.push('=');
+                san.push(promotion.to_char(PieceColor::White));
+            }
+        } else {
+            san.push(piece.to_char(PieceColor::White));
+            san.push_str(&self.disambiguation(piece, position));
+
+            if is_capture {
+                san.push('x');
+            }
+
+            san.push_str(&self.to.to_string());
+        }
+
+        let mut after = position.clone();
+        after.make_move(*self);
+
+        if after.is_in_check(after.side_to_move()) {
+            san.push(if after.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+
+        san
+    }
+
+    /// The file, rank or whole square needed to tell this move apart from
+    /// other pieces of the same kind that can reach the same square.
+    fn disambiguation(&self, piece: Piece, position: &Position) -> String {
+        let rivals: Vec<Move> = position
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| {
+                mv.to == self.to
+                    && mv.from != self.from
+                    && position.piece_at(mv.from).map(|(p, _)| p) == Some(piece)
+            })
+            .collect();
+
+        let file = (b'a' + self.from.file()) as char;
+        let rank = (b'1' + self.from.rank()) as char;
+
+        if rivals.is_empty() {
+            String::new()
+        } else if rivals.iter().all(|mv| mv.from.file() != self.from.file()) {
+            file.to_string()
+        } else if rivals.iter().all(|mv| mv.from.rank() != self.from.rank()) {
+            rank.to_string()
+        } else {
+            self.from.to_string()
+        }
+    }
+}
+
+impl Position {
+    /// Finds the legal move that `uci`, in long algebraic notation, describes.
+    pub fn parse_uci(&self, uci: &str) -> Result<Move, ParseMoveError> {
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return Err(ParseMoveError::Invalid);
+        }
+
+        let from = Square::from_algebraic(&uci[..2]).ok_or(ParseMoveError::Invalid)?;
+        let to = Square::from_algebraic(&uci[2..4]).ok_or(ParseMoveError::Invalid)?;
+
+        let promotion = match uci[4..].chars().next() {
+            None => None,
+            Some(c @ ('q' | 'r' | 'b' | 'n')) => Some(Piece::from_char(c).unwrap().0),
+            Some(_) => return Err(ParseMoveError::Invalid),
+        };
+
+        let mv = Move {
+            promotion,
+            ..Move::new(from, to)
+        };
+
+        self.legal_moves()
+            .into_iter()
+            .find(|&legal| legal == mv)
+            .ok_or(ParseMoveError::Illegal)
+    }
+
+    /// Finds the legal move that `san` describes. Check and annotation
+    /// suffixes like `+`, `#` or `!?` are ignored, as is a missing `=`
+    /// before the promotion piece; castling may be written with zeros.
+    pub fn parse_san(&self, san: &str) -> Result<Move, ParseMoveError> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+
+        let (piece, from_file, from_rank, to, promotion) = match san {
+            "O-O" | "0-0" | "O-O-O" | "0-0-0" => {
+                let king = self
+                    .king_square(self.side_to_move())
+                    .ok_or(ParseMoveError::Illegal)?;
+                let file = if san.len() == 3 { 6 } else { 2 };
+
+                (
+                    Piece::King,
+                    None,
+                    None,
+                    Square::new(file, king.rank()),
+                    None,
+                )
+            }
+
+            _ => parse_san_parts(san).ok_or(ParseMoveError::Invalid)?,
+        };
+
+        let mut matches = self.legal_moves().into_iter().filter(|mv| {
+            mv.to == to
+                && mv.promotion == promotion
+                && self.piece_at(mv.from).map(|(p, _)| p) == Some(piece)
+                && from_file.is_none_or(|file| mv.from.file() == file)
+                && from_rank.is_none_or(|rank| mv.from.rank() == rank)
+        });
+
+        match (matches.next(), matches.next()) {
+            (Some(mv), None) => Ok(mv),
+            (None, _) => Err(ParseMoveError::Illegal),
+            (Some(_), Some(_)) => Err(ParseMoveError::Ambiguous),
+        }
+    }
+}
+
+/// Splits a non-castling SAN move into the moving piece, the from file and
+/// rank if given, the destination and the promotion piece.
+#[allow(clippy::type_complexity)]
+fn parse_san_parts(san: &str) -> Option<(Piece, Option<u8>, Option<u8>, Square, Option<Piece>)> {
+    let mut rest = san;
+
+    let piece = match rest.chars().next()? {
+        c @ ('K' | 'Q' | 'R' | 'B' | 'N') => {
+            rest = &rest[1..];
+            Piece::from_char(c)?.0
+        }
+
+        _ => Piece::Pawn,
+    };
+
+    let mut promotion = None;
+    if piece == Piece::Pawn {
+        if let Some(c @ ('Q' | 'R' | 'B' | 'N')) = rest.chars().last() {
+            promotion = Some(Piece::from_char(c)?.0);
+            rest = rest[..rest.len() - 1].trim_end_matches('=');
+        }
+    }
+
+    if rest.len() < 2 || !rest.is_ascii() {
+        return None;
+    }
+
+    let (prefix, destination) = rest.split_at(rest.len() - 2);
+    let to = Square::from_algebraic(destination)?;
+
+    let mut from_file = None;
+    let mut from_rank = None;
+
+    for c in prefix.trim_end_matches('x').chars() {
+        match c {
+            'a'..='h' if from_file.is_none() => from_file = Some(c as u8 - b'a'),
+            '1'..='8' if from_rank.is_none() => from_rank = Some(c as u8 - b'1'),
+
+            _ => return None,
+        }
+    }
+
+    // A pawn only ever names its file, and only when capturing
+    if piece == Piece::Pawn && (from_rank.is_some() || from_file.is_some() != prefix.ends_with('x'))
+    {
+        return None;
+    }
+
+    Some((piece, from_file, from_rank, to, promotion))
+}
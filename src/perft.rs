@@ -0,0 +1,62 @@
+//! Move path enumeration, for checking move generation against known node
+//! counts.
+
+use crate::moves::Move;
+use crate::position::Position;
+
+impl Position {
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let mut position = self.clone();
+        perft(&mut position, depth)
+    }
+
+    /// [`Position::perft`] split up by the first move, in move generation
+    /// order. At depth 0 no move is played, so there is nothing to split.
+    pub fn divide(&self, depth: u32) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return vec![];
+        }
+
+        let mut position = self.clone();
+
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let undo = position.make_move(mv);
+                let nodes = if depth > 1 {
+                    perft(&mut position, depth - 1)
+                } else {
+                    1
+                };
+                position.unmake_move(mv, undo);
+
+                (mv, nodes)
+            })
+            .collect()
+    }
+}
+
+fn perft(position: &mut Position, depth: u32) -> u64 {
+    let moves = position.legal_moves();
+
+    // The moves themselves are the leaves, no need to play them
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .into_iter()
+        .map(|mv| {
+            let undo = position.make_move(mv);
+            let nodes = perft(position, depth - 1);
+            position.unmake_move(mv, undo);
+
+            nodes
+        })
+        .sum()
+}
@@ -0,0 +1,620 @@
+//! Portable Game Notation import and export.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::consts::START_FEN;
+use crate::fen::FenError;
+use crate::game::{Game, GameStatus};
+use crate::moves::Move;
+use crate::notation::ParseMoveError;
+use crate::piece::PieceColor;
+use crate::position::Position;
+
+/// The tag pairs written at the top of a PGN game. The seven-tag roster
+/// always comes first; `extra` follows in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tags {
+    pub event: String,
+    pub site: String,
+    /// `YYYY.MM.DD`, with `??` for unknown parts.
+    pub date: String,
+    pub round: String,
+    pub white: String,
+    pub black: String,
+    pub extra: Vec<(String, String)>,
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        Self {
+            event: "?".into(),
+            site: "?".into(),
+            date: "????.??.??".into(),
+            round: "?".into(),
+            white: "?".into(),
+            black: "?".into(),
+            extra: vec![],
+        }
+    }
+}
+
+/// Movetext lines are wrapped before this many characters.
+const LINE_WIDTH: usize = 80;
+
+impl GameStatus {
+    /// The PGN result token: `1-0`, `0-1`, `1/2-1/2`, or `*` for a game that
+    /// has not finished.
+    pub fn result(&self) -> &'static str {
+        match (self.is_over(), self.winner()) {
+            (false, _) => "*",
+            (true, Some(PieceColor::White)) => "1-0",
+            (true, Some(PieceColor::Black)) => "0-1",
+            (true, None) => "1/2-1/2",
+        }
+    }
+}
+
+impl Game {
+    /// Writes the game as PGN. A game that did not start from the standard
+    /// position gets `SetUp` and `FEN` tags.
+    pub fn to_pgn(&self, tags: &Tags) -> String {
+        let result = self.status().result();
+        let mut pgn = String::new();
+
+        let start_fen = self.start_position().to_fen();
+        let mut tag_pairs = vec![
+            ("Event", tags.event.as_str()),
+            ("Site", &tags.site),
+            ("Date", &tags.date),
+            ("Round", &tags.round),
+            ("White", &tags.white),
+            ("Black", &tags.black),
+            ("Result", result),
+        ];
+
+        if start_fen != START_FEN {
+            tag_pairs.push(("SetUp", "1"));
+            tag_pairs.push(("FEN", &start_fen));
+        }
+
+        tag_pairs.extend(tags.extra.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+        for (name, value) in tag_pairs {
+            pgn.push_str(&format!("[{name} \"{}\"]\n", escape(value)));
+        }
+
+        pgn.push('\n');
+
+        let mut tokens = vec![];
+        let mut position = self.start_position().clone();
+
+        for (i, &mv) in self.moves().iter().enumerate() {
+            let number = position.fullmove_number();
+
+            match position.side_to_move() {
+                PieceColor::White => tokens.push(format!("{number}.")),
+                // A game starting with Black to move still numbers its first move
+                PieceColor::Black if i == 0 => tokens.push(format!("{number}...")),
+                PieceColor::Black => {}
+            }
+
+            tokens.push(mv.to_san(&position));
+            position.make_move(mv);
+        }
+
+        tokens.push(result.to_string());
+
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len > 0 && line_len + 1 + token.len() >= LINE_WIDTH {
+                pgn.push('\n');
+                line_len = 0;
+            } else if line_len > 0 {
+                pgn.push(' ');
+                line_len += 1;
+            }
+
+            pgn.push_str(&token);
+            line_len += token.len();
+        }
+
+        pgn.push_str("\n\n");
+        pgn
+    }
+}
+
+/// Tag values are quoted, so quotes and backslashes inside need escaping.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// A game read from PGN, with its variations and annotations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnGame {
+    /// Every tag pair in the order given, the seven-tag roster included.
+    pub tags: Vec<(String, String)>,
+    /// The standard position, or the one in the `FEN` tag.
+    pub start: Position,
+    pub moves: Vec<PgnMove>,
+    /// The result token closing the movetext, `*` if there was none.
+    pub result: String,
+}
+
+/// A move in a [`PgnGame`] tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnMove {
+    pub mv: Move,
+    /// Numeric annotation glyphs; `!` and friends are stored as their
+    /// equivalents 1 to 6.
+    pub nags: Vec<u8>,
+    pub comment_before: Option<String>,
+    pub comment: Option<String>,
+    /// Lines played instead of this move, from the same position.
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+/// A step off a line of a [`PgnGame`]: into variation `index` of the move
+/// at `ply`, counting plies from the start of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Branch {
+    pub ply: usize,
+    pub index: usize,
+}
+
+/// Why a PGN file could not be read, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnError {
+    /// Counting from 1.
+    pub game: usize,
+    /// The ply of the offending move within the game, counting from 1, or 0
+    /// for an error before the first move.
+    pub ply: usize,
+    pub kind: PgnErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnErrorKind {
+    InvalidMove(String),
+    IllegalMove(String),
+    AmbiguousMove(String),
+    InvalidFen(FenError),
+    InvalidTag,
+    UnterminatedComment,
+    UnterminatedVariation,
+    /// A token that makes no sense where it stands, like a stray `)`.
+    UnexpectedToken(String),
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "game {}, ply {}: {}", self.game, self.ply, self.kind)
+    }
+}
+
+impl fmt::Display for PgnErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMove(san) => write!(f, "'{san}' is not a move"),
+            Self::IllegalMove(san) => write!(f, "illegal move '{san}'"),
+            Self::AmbiguousMove(san) => write!(f, "ambiguous move '{san}'"),
+            Self::InvalidFen(e) => write!(f, "invalid FEN tag: {e}"),
+            Self::InvalidTag => write!(f, "malformed tag pair"),
+            Self::UnterminatedComment => write!(f, "comment is never closed"),
+            Self::UnterminatedVariation => write!(f, "variation is never closed"),
+            Self::UnexpectedToken(token) => write!(f, "unexpected '{token}'"),
+        }
+    }
+}
+
+impl Error for PgnError {}
+
+impl PgnGame {
+    /// The value of the tag called `name`.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The main line as a [`Game`]. A result the moves themselves do not
+    /// reach is kept as [`GameStatus::Recorded`], since the file does not
+    /// say how the game ended.
+    pub fn to_game(&self) -> Game {
+        self.variation(&[])
+            .expect("the main line needs no branches")
+    }
+
+    /// The moves from the start of the game along the line reached by
+    /// taking `path`'s branches in turn, or `None` if one of them does not
+    /// exist. An empty `path` gives the main line.
+    pub fn line(&self, path: &[Branch]) -> Option<Vec<&PgnMove>> {
+        let mut moves = vec![];
+        let mut line = &self.moves;
+        let mut start = 0;
+
+        for branch in path {
+            let at = branch.ply.checked_sub(start)?;
+            let variation = line.get(at)?.variations.get(branch.index)?;
+
+            moves.extend(&line[..at]);
+            line = variation;
+            start = branch.ply;
+        }
+
+        moves.extend(line);
+        Some(moves)
+    }
+
+    /// The line `path` leads to, as a [`Game`]. Only the main line ends
+    /// with the file's result; a variation was never played out.
+    pub fn variation(&self, path: &[Branch]) -> Option<Game> {
+        let mut game = Game::from_position(self.start.clone());
+
+        for mv in self.line(path)? {
+            // Every move was checked against the legal moves while parsing
+            let _ = game.play(mv.mv);
+        }
+
+        if path.is_empty() {
+            match self.result.as_str() {
+                "1-0" => game.record_result(Some(PieceColor::White)),
+                "0-1" => game.record_result(Some(PieceColor::Black)),
+                "1/2-1/2" => game.record_result(None),
+
+                _ => {}
+            }
+        }
+
+        Some(game)
+    }
+}
+
+/// Reads every game in `pgn`.
+pub fn parse_pgn(pgn: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut parser = Parser {
+        lexer: Lexer { text: pgn, pos: 0 },
+        peeked: None,
+        game: 0,
+    };
+
+    let mut games = vec![];
+
+    while parser.peek(0)?.is_some() {
+        parser.game += 1;
+        games.push(parser.game()?);
+    }
+
+    Ok(games)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token<'a> {
+    Tag(&'a str, String),
+    Comment(&'a str),
+    Nag(u8),
+    Open,
+    Close,
+    Result(&'a str),
+    Symbol(&'a str),
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// Skips whitespace, `;` comments and `%` escape lines.
+    fn skip_blank(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            let at_line_start = self.pos == 0 || self.text[..self.pos].ends_with('\n');
+
+            if trimmed.starts_with(';') || (at_line_start && trimmed.starts_with('%')) {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Consumes the longest prefix whose characters satisfy `pred`.
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn next(&mut self) -> Result<Option<Token<'a>>, PgnErrorKind> {
+        self.skip_blank();
+
+        let Some(c) = self.rest().chars().next() else {
+            return Ok(None);
+        };
+
+        let token = match c {
+            '[' => {
+                self.pos += 1;
+                self.skip_blank();
+                let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                self.skip_blank();
+
+                if name.is_empty() || !self.rest().starts_with('"') {
+                    return Err(PgnErrorKind::InvalidTag);
+                }
+
+                self.pos += 1;
+                let mut value = String::new();
+                let mut chars = self.rest().char_indices();
+
+                loop {
+                    match chars.next() {
+                        Some((i, '"')) => {
+                            self.pos += i + 1;
+                            break;
+                        }
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => value.push(c),
+                            None => return Err(PgnErrorKind::InvalidTag),
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(PgnErrorKind::InvalidTag),
+                    }
+                }
+
+                self.skip_blank();
+                if !self.rest().starts_with(']') {
+                    return Err(PgnErrorKind::InvalidTag);
+                }
+
+                self.pos += 1;
+                Token::Tag(name, value)
+            }
+
+            '{' => {
+                let rest = &self.rest()[1..];
+                let end = rest.find('}').ok_or(PgnErrorKind::UnterminatedComment)?;
+                self.pos += end + 2;
+                Token::Comment(rest[..end].trim())
+            }
+
+            '$' => {
+                self.pos += 1;
+                let digits = self.take_while(|c| c.is_ascii_digit());
+                let nag = digits
+                    .parse()
+                    .map_err(|_| PgnErrorKind::UnexpectedToken(format!("${digits}")))?;
+                Token::Nag(nag)
+            }
+
+            '(' | ')' | '*' => {
+                self.pos += 1;
+                match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Result("*"),
+                }
+            }
+
+            _ => {
+                let symbol =
+                    self.take_while(|c| c.is_ascii_alphanumeric() || "+#=:-/_!?.".contains(c));
+
+                match symbol {
+                    "" => {
+                        let unexpected = c.to_string();
+                        self.pos += c.len_utf8();
+                        return Err(PgnErrorKind::UnexpectedToken(unexpected));
+                    }
+
+                    "1-0" | "0-1" | "1/2-1/2" => Token::Result(symbol),
+                    _ => Token::Symbol(symbol),
+                }
+            }
+        };
+
+        Ok(Some(token))
+    }
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<Token<'a>>,
+    game: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Looks at the next token; `ply` only places a lexing error.
+    fn peek(&mut self, ply: usize) -> Result<Option<&Token<'a>>, PgnError> {
+        if self.peeked.is_none() {
+            self.peeked = self.lexer.next().map_err(|kind| self.error(ply, kind))?;
+        }
+
+        Ok(self.peeked.as_ref())
+    }
+
+    fn bump(&mut self) -> Option<Token<'a>> {
+        self.peeked.take()
+    }
+
+    fn error(&self, ply: usize, kind: PgnErrorKind) -> PgnError {
+        PgnError {
+            game: self.game,
+            ply,
+            kind,
+        }
+    }
+
+    fn game(&mut self) -> Result<PgnGame, PgnError> {
+        let mut tags = vec![];
+
+        while let Some(Token::Tag(..)) = self.peek(0)? {
+            let Some(Token::Tag(name, value)) = self.bump() else {
+                unreachable!()
+            };
+
+            tags.push((name.to_string(), value));
+        }
+
+        let start = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => {
+                Position::from_fen(fen).map_err(|e| self.error(0, PgnErrorKind::InvalidFen(e)))?
+            }
+            None => Position::start(),
+        };
+
+        let moves = self.line(start.clone(), 0, false)?;
+
+        let result = match self.peek(0)? {
+            Some(Token::Result(result)) => {
+                let result = result.to_string();
+                self.bump();
+                result
+            }
+
+            _ => "*".to_string(),
+        };
+
+        Ok(PgnGame {
+            tags,
+            start,
+            moves,
+            result,
+        })
+    }
+
+    /// Reads moves from `position`, which `ply` plies into the game, up to
+    /// the end of the game or, if `nested`, the `)` closing the variation.
+    fn line(
+        &mut self,
+        mut position: Position,
+        mut ply: usize,
+        nested: bool,
+    ) -> Result<Vec<PgnMove>, PgnError> {
+        let mut moves: Vec<PgnMove> = vec![];
+        let mut before_last = position.clone();
+        let mut pending_comment: Option<String> = None;
+
+        loop {
+            let token = match self.peek(ply + 1)? {
+                None | Some(Token::Result(_) | Token::Tag(..)) if nested => {
+                    return Err(self.error(ply, PgnErrorKind::UnterminatedVariation));
+                }
+
+                None | Some(Token::Result(_) | Token::Tag(..)) => return Ok(moves),
+
+                Some(_) => self.bump().unwrap(),
+            };
+
+            match token {
+                Token::Symbol(symbol) => {
+                    let Some((san, nag)) = split_symbol(symbol) else {
+                        continue;
+                    };
+
+                    let mv = position.parse_san(san).map_err(|e| {
+                        let san = san.to_string();
+                        let kind = match e {
+                            ParseMoveError::Invalid => PgnErrorKind::InvalidMove(san),
+                            ParseMoveError::Illegal => PgnErrorKind::IllegalMove(san),
+                            ParseMoveError::Ambiguous => PgnErrorKind::AmbiguousMove(san),
+                        };
+
+                        self.error(ply + 1, kind)
+                    })?;
+
+                    before_last = position.clone();
+                    position.make_move(mv);
+                    ply += 1;
+
+                    moves.push(PgnMove {
+                        mv,
+                        nags: nag.into_iter().collect(),
+                        comment_before: pending_comment.take(),
+                        comment: None,
+                        variations: vec![],
+                    });
+                }
+
+                Token::Nag(nag) => {
+                    if let Some(last) = moves.last_mut() {
+                        last.nags.push(nag);
+                    }
+                }
+
+                Token::Comment(text) => {
+                    let comment = match moves.last_mut() {
+                        Some(last) if pending_comment.is_none() => &mut last.comment,
+                        _ => &mut pending_comment,
+                    };
+
+                    match comment {
+                        Some(existing) => {
+                            existing.push(' ');
+                            existing.push_str(text);
+                        }
+                        None => *comment = Some(text.to_string()),
+                    }
+                }
+
+                Token::Open => {
+                    if moves.is_empty() {
+                        return Err(self.error(ply, PgnErrorKind::UnexpectedToken("(".into())));
+                    }
+
+                    // A variation replaces the move before it
+                    let variation = self.line(before_last.clone(), ply - 1, true)?;
+                    moves.last_mut().unwrap().variations.push(variation);
+                }
+
+                Token::Close if nested => return Ok(moves),
+
+                Token::Close => {
+                    return Err(self.error(ply, PgnErrorKind::UnexpectedToken(")".into())));
+                }
+
+                Token::Result(_) | Token::Tag(..) => unreachable!(),
+            }
+        }
+    }
+}
+
+/// Splits a movetext symbol into the SAN move and the NAG its `!`/`?`
+/// suffix stands for. Returns `None` for a move number like `12.` or `12...`.
+fn split_symbol(symbol: &str) -> Option<(&str, Option<u8>)> {
+    // Move numbers may run straight into the move, as in `1.e4`
+    let symbol = match symbol.trim_start_matches(|c: char| c.is_ascii_digit()) {
+        rest if rest.starts_with('.') => rest.trim_start_matches('.'),
+        _ => symbol,
+    };
+
+    if symbol.is_empty() {
+        return None;
+    }
+
+    let end = symbol.find(['!', '?']).unwrap_or(symbol.len());
+    let (san, suffix) = symbol.split_at(end);
+
+    let nag = match suffix {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+
+        _ => None,
+    };
+
+    Some((san, nag))
+}
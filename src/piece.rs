@@ -1,5 +1,3 @@
-use sfml::graphics::Color;
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PieceColor {
     White,
@@ -7,17 +5,18 @@ pub enum PieceColor {
 }
 
 impl PieceColor {
-    pub fn as_color(&self) -> Color {
-        match self {
-            PieceColor::White => Color::rgb(170, 150, 150),
-            PieceColor::Black => Color::rgb(90, 70, 70),
-        }
-    }
-
     #[inline(always)]
     pub fn is_opposite(&self, other: &Self) -> bool {
         self != other
     }
+
+    #[inline(always)]
+    pub fn opposite(&self) -> Self {
+        match self {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -33,6 +32,23 @@ pub enum Piece {
 }
 
 impl Piece {
+    /// The pieces a pawn may promote to, strongest first.
+    pub const PROMOTIONS: [Self; 4] = [Self::Queen, Self::Rook, Self::Bishop, Self::Knight];
+
+    /// The material value in centipawns. The king can never be traded, so
+    /// it is worth nothing here.
+    pub const fn value(&self) -> i32 {
+        match self {
+            Self::Queen => 900,
+            Self::Rook => 500,
+            Self::Bishop => 330,
+            Self::Knight => 320,
+            Self::Pawn => 100,
+
+            Self::King | Self::None => 0,
+        }
+    }
+
     pub fn from_u8(n: u8) -> Option<Self> {
         if n > 5 {
             return None;
@@ -49,4 +65,45 @@ impl Piece {
             _ => unreachable!(),
         })
     }
+
+    /// Parses a FEN piece letter; uppercase is white, lowercase is black.
+    pub fn from_char(c: char) -> Option<(Self, PieceColor)> {
+        let piece = match c.to_ascii_lowercase() {
+            'k' => Self::King,
+            'q' => Self::Queen,
+            'b' => Self::Bishop,
+            'n' => Self::Knight,
+            'r' => Self::Rook,
+            'p' => Self::Pawn,
+
+            _ => return None,
+        };
+
+        let color = if c.is_ascii_lowercase() {
+            PieceColor::Black
+        } else {
+            PieceColor::White
+        };
+
+        Some((piece, color))
+    }
+
+    /// The FEN letter for this piece, uppercase for white.
+    pub fn to_char(&self, color: PieceColor) -> char {
+        let c = match self {
+            Self::King => 'k',
+            Self::Queen => 'q',
+            Self::Bishop => 'b',
+            Self::Knight => 'n',
+            Self::Rook => 'r',
+            Self::Pawn => 'p',
+
+            Self::None => '.',
+        };
+
+        match color {
+            PieceColor::White => c.to_ascii_uppercase(),
+            PieceColor::Black => c,
+        }
+    }
 }
@@ -0,0 +1,404 @@
+use crate::attacks;
+use crate::bitboard::Bitboard;
+use crate::consts::*;
+use crate::moves::{Move, Undo};
+use crate::piece::*;
+use crate::square::Square;
+use crate::zobrist;
+
+/// A chess position, independent of any rendering.
+///
+/// Pieces are kept as bitboards, one per piece type and one per color, for
+/// move generation, and as a square-indexed array for quick lookups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    board: [Option<(Piece, PieceColor)>; 64],
+    pieces: [Bitboard; 6],
+    colors: [Bitboard; 2],
+    pub(crate) side_to_move: PieceColor,
+    castling: CastlingRights,
+    pub(crate) en_passant: Option<Square>,
+    pub(crate) halfmove_clock: u32,
+    pub(crate) fullmove_number: u32,
+    /// Zobrist hash of the pieces and castling rights, kept up to date as
+    /// they change. The side to move and en passant are folded in by
+    /// [`Position::hash`].
+    hash: u64,
+}
+
+/// Which castling moves are still available, as in the third FEN field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
+
+impl CastlingRights {
+    pub const ALL: Self = Self {
+        white_kingside: true,
+        white_queenside: true,
+        black_kingside: true,
+        black_queenside: true,
+    };
+
+    pub const NONE: Self = Self {
+        white_kingside: false,
+        white_queenside: false,
+        black_kingside: false,
+        black_queenside: false,
+    };
+
+    pub fn kingside(&self, color: PieceColor) -> bool {
+        match color {
+            PieceColor::White => self.white_kingside,
+            PieceColor::Black => self.black_kingside,
+        }
+    }
+
+    pub fn queenside(&self, color: PieceColor) -> bool {
+        match color {
+            PieceColor::White => self.white_queenside,
+            PieceColor::Black => self.black_queenside,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    pub fn remove(&mut self, color: PieceColor) {
+        match color {
+            PieceColor::White => {
+                self.white_kingside = false;
+                self.white_queenside = false;
+            }
+
+            PieceColor::Black => {
+                self.black_kingside = false;
+                self.black_queenside = false;
+            }
+        }
+    }
+
+    /// Drops the rights that depend on a king or rook standing on `square`,
+    /// for when a piece moves from or to it.
+    pub fn touch(&mut self, square: Square) {
+        match (square.file(), square.rank()) {
+            (4, 0) => self.remove(PieceColor::White),
+            (4, 7) => self.remove(PieceColor::Black),
+            (7, 0) => self.white_kingside = false,
+            (0, 0) => self.white_queenside = false,
+            (7, 7) => self.black_kingside = false,
+            (0, 7) => self.black_queenside = false,
+
+            _ => {}
+        }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Position {
+    /// A position with no pieces on it.
+    pub fn empty() -> Self {
+        Self {
+            board: [None; 64],
+            pieces: [Bitboard::EMPTY; 6],
+            colors: [Bitboard::EMPTY; 2],
+            side_to_move: PieceColor::White,
+            castling: CastlingRights::NONE,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+        }
+    }
+
+    /// The standard starting position.
+    pub fn start() -> Self {
+        Self::from_fen(START_FEN).expect("START_FEN is valid")
+    }
+
+    pub fn side_to_move(&self) -> PieceColor {
+        self.side_to_move
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+
+    pub(crate) fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.hash ^= zobrist::castling(self.castling) ^ zobrist::castling(rights);
+        self.castling = rights;
+    }
+
+    /// A 64-bit Zobrist key identifying the position by its pieces, side to
+    /// move, castling rights and en passant file. Like Polyglot, the en
+    /// passant file only counts when a pawn stands ready to take.
+    pub fn hash(&self) -> u64 {
+        let mut hash = self.hash ^ zobrist::side(self.side_to_move);
+
+        if let Some(target) = self.en_passant {
+            let color = self.side_to_move;
+            let pawns = self.pieces(Piece::Pawn, color);
+
+            // A pawn that could take stands where a pawn of the other color
+            // on the target square would attack
+            if !(attacks::pawn_attacks(color.opposite(), target) & pawns).is_empty() {
+                hash ^= zobrist::en_passant(target.file());
+            }
+        }
+
+        hash
+    }
+
+    /// The square a pawn skipped over with a double push on the last move.
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    /// Plies since the last capture or pawn move, for the fifty-move rule.
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Starts at 1 and is incremented after each black move.
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// Returns the piece standing on `square`, if any.
+    #[inline(always)]
+    pub fn piece_at(&self, square: Square) -> Option<(Piece, PieceColor)> {
+        self.board[square.index()]
+    }
+
+    /// The squares holding a `piece` of `color`.
+    #[inline(always)]
+    pub fn pieces(&self, piece: Piece, color: PieceColor) -> Bitboard {
+        self.pieces[piece as usize] & self.colors[color as usize]
+    }
+
+    /// The squares holding any piece of `color`.
+    #[inline(always)]
+    pub fn color(&self, color: PieceColor) -> Bitboard {
+        self.colors[color as usize]
+    }
+
+    #[inline(always)]
+    pub fn occupied(&self) -> Bitboard {
+        self.colors[0] | self.colors[1]
+    }
+
+    /// Whether neither side has enough material left to ever checkmate:
+    /// bare kings, a single minor piece, or only bishops all on squares of
+    /// one color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy = [Piece::Queen, Piece::Rook, Piece::Pawn];
+        let colors = [PieceColor::White, PieceColor::Black];
+
+        if heavy
+            .iter()
+            .any(|&p| colors.iter().any(|&c| !self.pieces(p, c).is_empty()))
+        {
+            return false;
+        }
+
+        let knights = self.pieces[Piece::Knight as usize];
+        let bishops = self.pieces[Piece::Bishop as usize];
+
+        match (knights.count(), bishops.count()) {
+            (0, 0) | (1, 0) | (0, 1) => true,
+
+            // Bishops that all run on the same color can never cover the
+            // king's escape squares
+            (0, _) => {
+                let light = Bitboard(0x55aa_55aa_55aa_55aa);
+                (bishops & light).is_empty() || (bishops & !light).is_empty()
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Whether `color` could ever checkmate by any series of legal moves,
+    /// which is what decides a flag fall under the FIDE rules. A lone minor
+    /// piece can, if the other side has men of its own to box their king in;
+    /// bishops all on squares of one color cannot when the other side has
+    /// nothing but bishops on those squares too.
+    pub fn has_mating_material(&self, color: PieceColor) -> bool {
+        if [Piece::Queen, Piece::Rook, Piece::Pawn]
+            .iter()
+            .any(|&p| !self.pieces(p, color).is_empty())
+        {
+            return true;
+        }
+
+        let them = color.opposite();
+        let blockers = self.color(them) & !self.pieces(Piece::King, them);
+
+        let knights = self.pieces(Piece::Knight, color);
+        let bishops = self.pieces(Piece::Bishop, color);
+
+        match (knights.count(), bishops.count()) {
+            (0, 0) => false,
+            (1, 0) => !blockers.is_empty(),
+
+            (0, _) => {
+                let all_bishops = self.pieces[Piece::Bishop as usize];
+                let light = Bitboard(0x55aa_55aa_55aa_55aa);
+
+                !(blockers & !all_bishops).is_empty()
+                    || (!(all_bishops & light).is_empty() && !(all_bishops & !light).is_empty())
+            }
+
+            _ => true,
+        }
+    }
+
+    /// Puts `piece` on `square`, or clears it when `piece` is `None`.
+    pub fn set_piece(&mut self, square: Square, piece: Option<(Piece, PieceColor)>) {
+        let bit = Bitboard::from_square(square);
+
+        if let Some((old, color)) = self.board[square.index()] {
+            self.pieces[old as usize] ^= bit;
+            self.colors[color as usize] ^= bit;
+            self.hash ^= zobrist::piece(old, color, square);
+        }
+
+        // `Piece::None` only ever meant an empty square
+        let piece = piece.filter(|(p, _)| *p != Piece::None);
+
+        if let Some((new, color)) = piece {
+            self.pieces[new as usize] |= bit;
+            self.colors[color as usize] |= bit;
+            self.hash ^= zobrist::piece(new, color, square);
+        }
+
+        self.board[square.index()] = piece;
+    }
+
+    /// Moves whatever stands on `from` to `to`, replacing anything there.
+    pub fn move_piece(&mut self, from: Square, to: Square) {
+        let piece = self.piece_at(from);
+
+        self.set_piece(from, None);
+        self.set_piece(to, piece);
+    }
+
+    /// Plays `mv` without checking that it is legal, updating the side to
+    /// move and the move clocks. Castling, en passant and double pushes are
+    /// recognised from the board, so the move's flags need not be set.
+    ///
+    /// Pass the returned [`Undo`] to [`Position::unmake_move`] to take the
+    /// move back.
+    pub fn make_move(&mut self, mv: Move) -> Undo {
+        let (piece, color) = self.piece_at(mv.from).expect("no piece on the from square");
+        let is_en_passant = piece == Piece::Pawn && Some(mv.to) == self.en_passant;
+
+        // The pawn taken en passant stands beside the moving one, not on `to`
+        let captured_square = if is_en_passant {
+            Square::new(mv.to.file(), mv.from.rank())
+        } else {
+            mv.to
+        };
+
+        let undo = Undo {
+            captured: self
+                .piece_at(captured_square)
+                .map(|(piece, color)| (piece, color, captured_square)),
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        };
+
+        self.set_piece(captured_square, None);
+        self.move_piece(mv.from, mv.to);
+        self.en_passant = None;
+
+        if let Some(promotion) = mv.promotion {
+            self.set_piece(mv.to, Some((promotion, color)));
+        }
+
+        if piece == Piece::Pawn && mv.from.rank().abs_diff(mv.to.rank()) == 2 {
+            self.en_passant = Some(Square::new(
+                mv.from.file(),
+                (mv.from.rank() + mv.to.rank()) / 2,
+            ));
+        }
+
+        if piece == Piece::King && is_castle(mv) {
+            let (rook_from, rook_to) = castle_rook_squares(mv);
+            self.move_piece(rook_from, rook_to);
+        }
+
+        let mut castling = self.castling;
+        castling.touch(mv.from);
+        castling.touch(mv.to);
+        self.set_castling_rights(castling);
+
+        if piece == Piece::Pawn || undo.captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if color == PieceColor::Black {
+            self.fullmove_number += 1;
+        }
+
+        self.side_to_move = color.opposite();
+
+        undo
+    }
+
+    /// Takes back `mv`, which must be the last move made on this position,
+    /// restoring it exactly as it was.
+    pub fn unmake_move(&mut self, mv: Move, undo: Undo) {
+        let (piece, color) = self.piece_at(mv.to).expect("no piece on the to square");
+
+        self.move_piece(mv.to, mv.from);
+
+        if mv.promotion.is_some() {
+            self.set_piece(mv.from, Some((Piece::Pawn, color)));
+        }
+
+        if piece == Piece::King && is_castle(mv) {
+            let (rook_from, rook_to) = castle_rook_squares(mv);
+            self.move_piece(rook_to, rook_from);
+        }
+
+        if let Some((piece, color, square)) = undo.captured {
+            self.set_piece(square, Some((piece, color)));
+        }
+
+        self.set_castling_rights(undo.castling);
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.side_to_move = color;
+    }
+}
+
+/// Castling is the only king move that covers two files.
+fn is_castle(mv: Move) -> bool {
+    mv.from.file().abs_diff(mv.to.file()) == 2
+}
+
+/// Where the rook starts and ends for the castling move `mv`.
+fn castle_rook_squares(mv: Move) -> (Square, Square) {
+    let rank = mv.from.rank();
+
+    if mv.to.file() == 6 {
+        (Square::new(7, rank), Square::new(5, rank))
+    } else {
+        (Square::new(0, rank), Square::new(3, rank))
+    }
+}
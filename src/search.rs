@@ -0,0 +1,603 @@
+//! Finding a good move to play.
+//!
+//! The search deepens one ply at a time with a negamax alpha-beta search,
+//! so it always has the best move of the last finished depth to fall back
+//! on when it is told to stop. Captures are played out to the end by a
+//! quiescence search before anything is evaluated, and what each depth
+//! learns (best moves in the transposition table, killer and history
+//! scores) orders the moves of the next so that cutoffs come early.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::eval::evaluate;
+use crate::moves::Move;
+use crate::piece::{Piece, PieceColor};
+use crate::position::Position;
+
+/// How deep the search ever goes, in plies.
+pub const MAX_DEPTH: u32 = 64;
+
+/// The score of being mated right now. Mates further away score closer to
+/// zero by one per ply, so the search prefers the quickest.
+const MATE: i32 = 30_000;
+
+/// Below this a score is a mate score rather than an evaluation.
+const MATE_BOUND: i32 = MATE - 2 * MAX_DEPTH as i32;
+
+/// Check the clock once every this many nodes.
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// The transposition table size when none is asked for, in megabytes.
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// When to stop searching. With nothing set the search runs until
+/// [`MAX_DEPTH`] or until it is stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    pub depth: Option<u32>,
+    /// Search for exactly this long.
+    pub movetime: Option<Duration>,
+    /// Time left on each side's clock, indexed by [`PieceColor`].
+    pub time: [Option<Duration>; 2],
+    /// Increment per move for each side, indexed by [`PieceColor`].
+    pub increment: [Duration; 2],
+    /// Moves until the next time control, if it is not sudden death.
+    pub moves_to_go: Option<u32>,
+    /// Search until stopped, whatever the other limits say.
+    pub infinite: bool,
+    /// Time kept back per move for communication delays.
+    pub overhead: Duration,
+}
+
+/// A search score from the side to move's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i32),
+    /// Mate in this many moves, negative when the side to move gets mated.
+    Mate(i32),
+}
+
+impl Score {
+    fn from_internal(score: i32) -> Self {
+        if score >= MATE_BOUND {
+            Self::Mate((MATE - score + 1) / 2)
+        } else if score <= -MATE_BOUND {
+            Self::Mate(-(MATE + score) / 2)
+        } else {
+            Self::Centipawns(score)
+        }
+    }
+}
+
+/// Progress after each finished depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: u32,
+    pub score: Score,
+    pub nodes: u64,
+    pub elapsed: Duration,
+    /// The principal variation, starting with the best move.
+    pub pv: Vec<Move>,
+}
+
+/// Decides when the search should stop on its own.
+///
+/// The soft limit is the time the move is budgeted; no new depth starts
+/// after half of it has gone, since the next depth would likely overrun.
+/// The hard limit cuts a depth short and only matters when one takes far
+/// longer than the last.
+#[derive(Debug, Clone, Copy)]
+struct TimeManager {
+    start: Instant,
+    soft: Option<Duration>,
+    hard: Option<Duration>,
+}
+
+impl TimeManager {
+    fn new(limits: &Limits, color: PieceColor) -> Self {
+        let start = Instant::now();
+
+        let (soft, hard) = if limits.infinite {
+            (None, None)
+        } else if let Some(movetime) = limits.movetime {
+            let time = movetime.saturating_sub(limits.overhead);
+            (Some(time), Some(time))
+        } else if let Some(left) = limits.time[color as usize] {
+            let left = left.saturating_sub(limits.overhead);
+            let moves = limits.moves_to_go.unwrap_or(30).clamp(1, 30);
+            let increment = limits.increment[color as usize];
+
+            // Spend a share of what is left plus most of the increment, but
+            // never so much that the clock cannot cover the rest
+            let soft = (left / moves + increment * 3 / 4).min(left / 2);
+            let hard = (soft * 4).min(left / 3).max(soft);
+
+            (Some(soft), Some(hard))
+        } else {
+            (None, None)
+        };
+
+        Self { start, soft, hard }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn out_of_time(&self) -> bool {
+        self.hard.is_some_and(|hard| self.elapsed() >= hard)
+    }
+
+    fn should_deepen(&self) -> bool {
+        self.soft.is_none_or(|soft| self.elapsed() < soft / 2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The score is at least this; the search failed high.
+    Lower,
+    /// The score is at most this; the search failed low.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    key: u64,
+    best: Option<Move>,
+    score: i32,
+    depth: u8,
+    bound: Bound,
+}
+
+/// What earlier searches learned about positions, keyed by Zobrist hash.
+/// A slot keeps whichever entry was searched deeper, or the newer one.
+struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    fn new(megabytes: usize) -> Self {
+        let len = (megabytes.max(1) << 20) / size_of::<Option<Entry>>();
+
+        Self {
+            entries: vec![None; len],
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+
+    fn get(&self, key: u64) -> Option<Entry> {
+        self.entries[self.index(key)].filter(|entry| entry.key == key)
+    }
+
+    fn store(&mut self, entry: Entry) {
+        let index = self.index(entry.key);
+
+        match self.entries[index] {
+            Some(old) if old.key == entry.key && old.depth > entry.depth => {}
+            _ => self.entries[index] = Some(entry),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+/// A search engine that keeps what it learns between moves of a game.
+pub struct Engine {
+    tt: TranspositionTable,
+    /// Two quiet moves per ply that caused a cutoff in a sibling node.
+    killers: Vec<[Option<Move>; 2]>,
+    /// How often a quiet move caused a cutoff, weighted by depth, indexed
+    /// by color, from and to.
+    history: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
+
+/// Per-search state.
+struct Context<'a> {
+    engine: &'a mut Engine,
+    stop: &'a AtomicBool,
+    time: TimeManager,
+    nodes: u64,
+    /// Set once the search runs out of time or is stopped; every score
+    /// after that is meaningless.
+    aborted: bool,
+    /// Hashes of every position from the start of the game up to the node
+    /// being searched, for spotting repetitions.
+    path: Vec<u64>,
+}
+
+impl Engine {
+    /// An engine with a transposition table of about `hash_mb` megabytes.
+    pub fn new(hash_mb: usize) -> Self {
+        Self {
+            tt: TranspositionTable::new(hash_mb),
+            killers: vec![[None; 2]; MAX_DEPTH as usize + 1],
+            history: Box::new([[[0; 64]; 64]; 2]),
+        }
+    }
+
+    /// Replaces the transposition table with one of about `hash_mb`
+    /// megabytes.
+    pub fn set_hash_size(&mut self, hash_mb: usize) {
+        self.tt = TranspositionTable::new(hash_mb);
+    }
+
+    /// Forgets everything learned, for a new game.
+    pub fn clear(&mut self) {
+        self.tt.clear();
+        self.killers.fill([None; 2]);
+        *self.history = [[[0; 64]; 64]; 2];
+    }
+
+    /// Searches `position` within `limits`, or until `stop` is set, calling
+    /// `report` after each finished depth. `history` holds the hashes of the
+    /// positions the game went through before this one, oldest first, so
+    /// that repetitions are scored as draws.
+    ///
+    /// Returns the best move found, or `None` if there are no legal moves.
+    pub fn search(
+        &mut self,
+        position: &Position,
+        history: &[u64],
+        limits: &Limits,
+        stop: &AtomicBool,
+        mut report: impl FnMut(&SearchInfo),
+    ) -> Option<Move> {
+        let moves = position.legal_moves();
+        let mut best = *moves.first()?;
+
+        // With one legal move there is nothing to think about
+        if moves.len() == 1 && !limits.infinite {
+            return Some(best);
+        }
+
+        self.killers.fill([None; 2]);
+
+        let mut context = Context {
+            time: TimeManager::new(limits, position.side_to_move()),
+            engine: self,
+            stop,
+            nodes: 0,
+            aborted: false,
+            path: history.to_vec(),
+        };
+
+        context.path.push(position.hash());
+
+        let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        let mut position = position.clone();
+
+        for depth in 1..=max_depth {
+            let score = context.negamax(&mut position, depth, 0, -MATE, MATE);
+
+            // A search cut short may not have looked at the best move at all
+            if context.aborted {
+                break;
+            }
+
+            let pv = context.engine.principal_variation(&position, depth);
+            best = pv.first().copied().unwrap_or(best);
+
+            report(&SearchInfo {
+                depth,
+                score: Score::from_internal(score),
+                nodes: context.nodes,
+                elapsed: context.time.elapsed(),
+                pv,
+            });
+
+            if score.abs() >= MATE_BOUND || !context.time.should_deepen() {
+                break;
+            }
+        }
+
+        Some(best)
+    }
+
+    /// Follows the best moves stored in the transposition table.
+    fn principal_variation(&self, position: &Position, depth: u32) -> Vec<Move> {
+        let mut position = position.clone();
+        let mut pv = vec![];
+        let mut seen = vec![];
+
+        while let Some(mv) = self.tt.get(position.hash()).and_then(|entry| entry.best) {
+            // Stop at a repetition, or the line would go round forever
+            if pv.len() >= depth as usize || seen.contains(&position.hash()) {
+                break;
+            }
+
+            if !position.legal_moves().contains(&mv) {
+                break;
+            }
+
+            seen.push(position.hash());
+            position.make_move(mv);
+            pv.push(mv);
+        }
+
+        pv
+    }
+}
+
+impl Context<'_> {
+    fn check_clock(&mut self) {
+        if self.nodes.is_multiple_of(CLOCK_INTERVAL) {
+            self.aborted |= self.time.out_of_time() || self.stop.load(Ordering::Relaxed);
+        }
+    }
+
+    /// Whether the position at the end of `path` has occurred before within
+    /// the last `halfmove_clock` plies.
+    fn is_repetition(&self, halfmove_clock: u32) -> bool {
+        let Some((&current, earlier)) = self.path.split_last() else {
+            return false;
+        };
+
+        earlier
+            .iter()
+            .rev()
+            .take(halfmove_clock as usize)
+            .skip(1)
+            .step_by(2)
+            .any(|&hash| hash == current)
+    }
+
+    fn negamax(
+        &mut self,
+        position: &mut Position,
+        mut depth: u32,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        self.check_clock();
+
+        if self.aborted {
+            return 0;
+        }
+
+        let key = position.hash();
+
+        if ply > 0
+            && (position.halfmove_clock() >= 100 || self.is_repetition(position.halfmove_clock()))
+        {
+            return 0;
+        }
+
+        let us = position.side_to_move();
+        let in_check = position.is_in_check(us);
+
+        // Look a ply further when in check, so the search never stops in
+        // the middle of a mating attack
+        if in_check {
+            depth += 1;
+        }
+
+        if depth == 0 || ply >= MAX_DEPTH as usize {
+            return self.quiescence(position, ply, alpha, beta);
+        }
+
+        let entry = self.engine.tt.get(key);
+
+        if let Some(entry) = entry.filter(|e| ply > 0 && e.depth as u32 >= depth) {
+            let score = score_from_tt(entry.score, ply);
+
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+
+            if usable {
+                return score;
+            }
+        }
+
+        let mut moves = position.legal_moves();
+
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+
+        self.order(position, &mut moves, entry.and_then(|e| e.best), ply);
+
+        let original_alpha = alpha;
+        let mut best = moves[0];
+        let mut best_score = -MATE;
+
+        for mv in moves {
+            let undo = position.make_move(mv);
+            self.path.push(position.hash());
+            let score = -self.negamax(position, depth - 1, ply + 1, -beta, -alpha);
+            self.path.pop();
+            position.unmake_move(mv, undo);
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best = mv;
+            }
+
+            if score > alpha {
+                alpha = score;
+            }
+
+            if alpha >= beta {
+                if !is_tactical(position, mv) {
+                    self.remember_cutoff(us, mv, depth, ply);
+                }
+
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+
+        self.engine.tt.store(Entry {
+            key,
+            best: Some(best),
+            score: score_to_tt(best_score, ply),
+            depth: depth as u8,
+            bound,
+        });
+
+        best_score
+    }
+
+    /// Searches only captures and promotions until the position is quiet,
+    /// so that an evaluation is never taken halfway through an exchange.
+    fn quiescence(
+        &mut self,
+        position: &mut Position,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        self.check_clock();
+
+        if self.aborted {
+            return 0;
+        }
+
+        let in_check = position.is_in_check(position.side_to_move());
+
+        // Out of check the side to move can always decline to capture
+        if !in_check {
+            let stand_pat = evaluate(position);
+
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+
+            alpha = alpha.max(stand_pat);
+        }
+
+        let mut moves = position.legal_moves();
+
+        if moves.is_empty() {
+            return if in_check { -MATE + ply as i32 } else { 0 };
+        }
+
+        // In check every evasion counts, or a quiet escape would be missed
+        if !in_check {
+            moves.retain(|&mv| is_tactical(position, mv));
+        }
+
+        self.order(position, &mut moves, None, ply);
+
+        for mv in moves {
+            let undo = position.make_move(mv);
+            let score = -self.quiescence(position, ply + 1, -beta, -alpha);
+            position.unmake_move(mv, undo);
+
+            if self.aborted {
+                return 0;
+            }
+
+            if score >= beta {
+                return score;
+            }
+
+            alpha = alpha.max(score);
+        }
+
+        alpha
+    }
+
+    /// Sorts `moves` so the likeliest best come first: the transposition
+    /// table's move, then captures by MVV-LVA, then killers, then quiet
+    /// moves by history.
+    fn order(&self, position: &Position, moves: &mut [Move], tt_move: Option<Move>, ply: usize) {
+        let color = position.side_to_move() as usize;
+        let killers = self.engine.killers.get(ply).copied().unwrap_or_default();
+
+        moves.sort_by_cached_key(|&mv| {
+            let score = if Some(mv) == tt_move {
+                1_000_000
+            } else if is_tactical(position, mv) {
+                // Most valuable victim, least valuable attacker
+                let victim = position
+                    .piece_at(mv.to)
+                    .map_or(Piece::Pawn.value(), |(piece, _)| piece.value());
+                let attacker = position
+                    .piece_at(mv.from)
+                    .map_or(0, |(piece, _)| piece.value());
+                let promotion = mv.promotion.map_or(0, |piece| piece.value());
+
+                100_000 + 10 * (victim + promotion) - attacker / 10
+            } else if Some(mv) == killers[0] {
+                90_000
+            } else if Some(mv) == killers[1] {
+                80_000
+            } else {
+                self.engine.history[color][mv.from.index()][mv.to.index()]
+            };
+
+            -score
+        });
+    }
+
+    /// Records a quiet move that caused a beta cutoff.
+    fn remember_cutoff(&mut self, color: PieceColor, mv: Move, depth: u32, ply: usize) {
+        if let Some(killers) = self.engine.killers.get_mut(ply) {
+            if killers[0] != Some(mv) {
+                killers[1] = killers[0];
+                killers[0] = Some(mv);
+            }
+        }
+
+        let history = &mut self.engine.history[color as usize][mv.from.index()][mv.to.index()];
+        *history = (*history + (depth * depth) as i32).min(50_000);
+    }
+}
+
+/// Captures and promotions, which the quiescence search plays out.
+fn is_tactical(position: &Position, mv: Move) -> bool {
+    mv.is_capture() || mv.promotion.is_some() || position.piece_at(mv.to).is_some()
+}
+
+/// Mate scores are stored relative to the node rather than the root, so
+/// that they stay right when the position is reached at another ply.
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply as i32
+    } else if score <= -MATE_BOUND {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply as i32
+    } else if score <= -MATE_BOUND {
+        score + ply as i32
+    } else {
+        score
+    }
+}
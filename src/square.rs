@@ -0,0 +1,68 @@
+use std::fmt;
+
+/// A square on the board, stored as `rank * 8 + file` (a1 = 0, h8 = 63).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Square(u8);
+
+impl Square {
+    /// `file` and `rank` are zero based, so `Square::new(4, 3)` is e4.
+    pub fn new(file: u8, rank: u8) -> Self {
+        debug_assert!(file < 8 && rank < 8);
+        Self(rank * 8 + file)
+    }
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        (index < 64).then_some(Self(index))
+    }
+
+    /// Parses a square in algebraic notation, like `e4`.
+    pub fn from_algebraic(s: &str) -> Option<Self> {
+        let &[file, rank] = s.as_bytes() else {
+            return None;
+        };
+
+        if !(b'a'..=b'h').contains(&file) || !(b'1'..=b'8').contains(&rank) {
+            return None;
+        }
+
+        Some(Self::new(file - b'a', rank - b'1'))
+    }
+
+    /// Returns the square `df` files and `dr` ranks away, or `None` if that
+    /// falls off the board.
+    pub fn offset(&self, df: i32, dr: i32) -> Option<Self> {
+        let file = self.file() as i32 + df;
+        let rank = self.rank() as i32 + dr;
+
+        if (0..8).contains(&file) && (0..8).contains(&rank) {
+            Some(Self::new(file as u8, rank as u8))
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    pub const fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    #[inline(always)]
+    pub fn file(&self) -> u8 {
+        self.0 % 8
+    }
+
+    #[inline(always)]
+    pub fn rank(&self) -> u8 {
+        self.0 / 8
+    }
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..64).map(Self)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file()) as char, self.rank() + 1)
+    }
+}
@@ -0,0 +1,298 @@
+//! The Universal Chess Interface, for running as an engine under a GUI or
+//! tournament manager.
+//!
+//! Commands arrive on stdin one per line; the search runs on its own thread
+//! so that `stop`, `isready` and `quit` are answered while it thinks.
+
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chess::search::{Engine, Limits, Score, SearchInfo, DEFAULT_HASH_MB};
+use chess::{PieceColor, Position};
+
+const NAME: &str = "chess";
+const AUTHOR: &str = "the chess authors";
+
+/// Milliseconds kept back on every move, unless set with `setoption`.
+const DEFAULT_OVERHEAD: u64 = 30;
+
+/// The most milliseconds `setoption` will keep back on every move.
+const MAX_OVERHEAD: u64 = 5000;
+
+/// The largest transposition table `setoption` will allocate, in megabytes.
+const MAX_HASH_MB: usize = 1024;
+
+pub fn run() {
+    let mut uci = Uci {
+        position: Position::start(),
+        history: vec![],
+        overhead: Duration::from_millis(DEFAULT_OVERHEAD),
+        engine: Some(Engine::default()),
+        search: None,
+    };
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+
+        let args: Vec<&str> = words.collect();
+
+        match command {
+            "uci" => {
+                println!("id name {NAME} {}", env!("CARGO_PKG_VERSION"));
+                println!("id author {AUTHOR}");
+                println!(
+                    "option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {MAX_HASH_MB}"
+                );
+                println!(
+                    "option name Move Overhead type spin default {DEFAULT_OVERHEAD} min 0 max {MAX_OVERHEAD}"
+                );
+                println!("uciok");
+            }
+
+            "isready" => println!("readyok"),
+
+            "ucinewgame" => {
+                uci.wait();
+                uci.position = Position::start();
+                uci.history.clear();
+                uci.engine().clear();
+            }
+
+            // The search works on its own copy, so it carries on undisturbed
+            "position" => match parse_position(&args) {
+                Ok((position, history)) => {
+                    uci.position = position;
+                    uci.history = history;
+                }
+                Err(e) => println!("info string {e}"),
+            },
+
+            "go" => {
+                uci.wait();
+                uci.go(parse_go(&args, uci.overhead));
+            }
+
+            "stop" => uci.stop(),
+
+            "setoption" => uci.set_option(&args),
+
+            "quit" => {
+                uci.stop();
+                return;
+            }
+
+            _ => println!("info string unknown command {command}"),
+        }
+    }
+
+    // At the end of a piped script, let the last search finish
+    uci.wait();
+}
+
+struct Uci {
+    position: Position,
+    /// Hashes of the positions before `position`, for repetitions.
+    history: Vec<u64>,
+    overhead: Duration,
+    /// Lent to the search thread while it runs.
+    engine: Option<Engine>,
+    search: Option<RunningSearch>,
+}
+
+struct RunningSearch {
+    stop: Arc<AtomicBool>,
+    infinite: bool,
+    /// Hands the engine back when the search is over.
+    thread: JoinHandle<Engine>,
+}
+
+impl Uci {
+    /// The engine, once any search has finished with it.
+    fn engine(&mut self) -> &mut Engine {
+        self.wait();
+        self.engine
+            .as_mut()
+            .expect("the engine is back once the search ends")
+    }
+
+    fn go(&mut self, limits: Limits) {
+        let stop = Arc::new(AtomicBool::new(false));
+        let position = self.position.clone();
+        let history = self.history.clone();
+        let mut engine = self.engine.take().expect("no search is running");
+
+        let thread = {
+            let stop = stop.clone();
+
+            thread::spawn(move || {
+                let best = engine.search(&position, &history, &limits, &stop, print_info);
+
+                // The protocol forbids answering an infinite search before
+                // `stop`, even when there is nothing left to search
+                while limits.infinite && !stop.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(1));
+                }
+
+                match best {
+                    Some(mv) => println!("bestmove {}", mv.to_uci()),
+                    None => println!("bestmove 0000"),
+                }
+
+                engine
+            })
+        };
+
+        self.search = Some(RunningSearch {
+            stop,
+            infinite: limits.infinite,
+            thread,
+        });
+    }
+
+    /// Stops the search, if one is running, and waits for its `bestmove`.
+    fn stop(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            self.engine = search.thread.join().ok();
+        }
+    }
+
+    /// Waits for the search, if one is running, to finish on its own. An
+    /// infinite search never would, so it is stopped.
+    fn wait(&mut self) {
+        if let Some(search) = self.search.take() {
+            if search.infinite {
+                search.stop.store(true, Ordering::Relaxed);
+            }
+
+            self.engine = search.thread.join().ok();
+        }
+    }
+
+    /// `setoption name <name> [value <value>]`; names may contain spaces.
+    fn set_option(&mut self, args: &[&str]) {
+        let value_at = args.iter().position(|&w| w == "value");
+        // `value` may come before the name, or there may be no words at all
+        let start = 1.min(args.len());
+        let name = args[start..value_at.unwrap_or(args.len()).max(start)].join(" ");
+        let value = value_at.map(|i| args[i + 1..].join(" "));
+
+        match (name.to_lowercase().as_str(), value) {
+            ("hash", Some(value)) => match value.parse() {
+                Ok(mb) => self
+                    .engine()
+                    .set_hash_size(usize::clamp(mb, 1, MAX_HASH_MB)),
+                Err(_) => println!("info string invalid Hash {value}"),
+            },
+
+            ("move overhead", Some(value)) => match value.parse() {
+                Ok(ms) => self.overhead = Duration::from_millis(u64::min(ms, MAX_OVERHEAD)),
+                Err(_) => println!("info string invalid Move Overhead {value}"),
+            },
+
+            _ => println!("info string unknown option {name}"),
+        }
+    }
+}
+
+fn print_info(info: &SearchInfo) {
+    let score = match info.score {
+        Score::Centipawns(cp) => format!("cp {cp}"),
+        Score::Mate(moves) => format!("mate {moves}"),
+    };
+
+    let pv: Vec<String> = info.pv.iter().map(|mv| mv.to_uci()).collect();
+    let ms = info.elapsed.as_millis();
+    let nps = info.nodes as u128 * 1000 / ms.max(1);
+
+    println!(
+        "info depth {} score {score} nodes {} nps {nps} time {ms} pv {}",
+        info.depth,
+        info.nodes,
+        pv.join(" ")
+    );
+}
+
+/// `position (startpos | fen <fen>) [moves <move>...]`, returning the
+/// position and the hashes of those before it.
+fn parse_position(args: &[&str]) -> Result<(Position, Vec<u64>), String> {
+    let moves_at = args
+        .iter()
+        .position(|&w| w == "moves")
+        .unwrap_or(args.len());
+
+    let mut position = match args.first() {
+        Some(&"startpos") => Position::start(),
+        Some(&"fen") => Position::from_fen(&args[1..moves_at].join(" "))
+            .map_err(|e| format!("invalid FEN: {e}"))?,
+
+        _ => return Err("expected startpos or fen".into()),
+    };
+
+    let mut history = vec![];
+
+    for uci in args.iter().skip(moves_at + 1) {
+        let mv = position
+            .parse_uci(uci)
+            .map_err(|e| format!("invalid move {uci}: {e}"))?;
+
+        history.push(position.hash());
+        position.make_move(mv);
+    }
+
+    Ok((position, history))
+}
+
+/// The arguments of `go`. Unknown ones, like `ponder`, are skipped.
+fn parse_go(args: &[&str], overhead: Duration) -> Limits {
+    let mut limits = Limits {
+        overhead,
+        ..Limits::default()
+    };
+
+    let mut args = args.iter();
+
+    while let Some(&arg) = args.next() {
+        if arg == "infinite" {
+            limits.infinite = true;
+            continue;
+        }
+
+        // Some GUIs send a negative time once the clock has run out
+        let Some(value) = args.clone().next().and_then(|v| v.parse::<i64>().ok()) else {
+            continue;
+        };
+
+        let value = value.max(0) as u64;
+
+        let ms = Duration::from_millis(value);
+        let white = PieceColor::White as usize;
+        let black = PieceColor::Black as usize;
+
+        match arg {
+            "depth" => limits.depth = Some(value as u32),
+            "movetime" => limits.movetime = Some(ms),
+            "wtime" => limits.time[white] = Some(ms),
+            "btime" => limits.time[black] = Some(ms),
+            "winc" => limits.increment[white] = ms,
+            "binc" => limits.increment[black] = ms,
+            "movestogo" => limits.moves_to_go = Some(value as u32),
+
+            _ => continue,
+        }
+
+        args.next();
+    }
+
+    limits
+}
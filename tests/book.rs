@@ -0,0 +1,152 @@
+//! Reading, writing and probing opening books.
+
+use chess::book::{BookError, Selection};
+use chess::{Book, Position};
+
+const CASTLING: &str = "r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1";
+
+fn san(position: &Position, book: &Book) -> Vec<(String, u16)> {
+    book.moves(position)
+        .iter()
+        .map(|book| (book.mv.to_san(position), book.weight))
+        .collect()
+}
+
+/// Plays `moves`, given in UCI notation, from the start.
+fn after(moves: &str) -> Position {
+    let mut position = Position::start();
+
+    for uci in moves.split_whitespace() {
+        let mv = position.parse_uci(uci).unwrap();
+        position.make_move(mv);
+    }
+
+    position
+}
+
+/// The reference keys published with the Polyglot book format.
+#[test]
+fn polyglot_keys() {
+    let keys = [
+        ("", 0x463b_9618_1691_fc9c),
+        ("e2e4", 0x823c_9b50_fd11_4196),
+        ("e2e4 d7d5", 0x0756_b944_61c5_0fb0),
+        ("e2e4 d7d5 e4e5", 0x662f_afb9_65db_29d4),
+        // En passant is possible, so the file counts
+        ("e2e4 d7d5 e4e5 f7f5", 0x22a4_8b5a_8e47_ff78),
+        ("e2e4 d7d5 e4e5 f7f5 e1e2", 0x652a_607c_a3f2_42c1),
+        ("e2e4 d7d5 e4e5 f7f5 e1e2 e8f7", 0x00fd_d303_c946_bdd9),
+        ("a2a4 b7b5 h2h4 b5b4 c2c4", 0x3c81_23ea_7b06_7637),
+        ("a2a4 b7b5 h2h4 b5b4 c2c4 b4c3 a1a3", 0x5c3f_9b82_9b27_9560),
+    ];
+
+    for (moves, key) in keys {
+        assert_eq!(after(moves).hash(), key, "after {moves:?}");
+    }
+}
+
+/// An entry as another tool would write it: 1.e4 from the start.
+#[test]
+fn reads_entries_from_other_tools() {
+    let mut bytes = 0x463b_9618_1691_fc9c_u64.to_be_bytes().to_vec();
+    // e4 (28) in the low six bits, e2 (12) in the next six
+    bytes.extend_from_slice(&(28_u16 | 12 << 6).to_be_bytes());
+    bytes.extend_from_slice(&[0, 7, 0, 0, 0, 0]);
+
+    let book = Book::from_bytes(&bytes).unwrap();
+    let start = Position::start();
+
+    assert_eq!(san(&start, &book), [("e4".into(), 7)]);
+}
+
+#[test]
+fn moves_are_heaviest_first() {
+    let start = Position::start();
+    let mut book = Book::default();
+
+    book.add(&start, start.parse_san("d4").unwrap(), 2);
+    book.add(&start, start.parse_san("e4").unwrap(), 3);
+    book.add(&start, start.parse_san("d4").unwrap(), 2);
+    book.add(&start, start.parse_san("Nf3").unwrap(), 1);
+
+    assert_eq!(book.len(), 3);
+    assert_eq!(
+        san(&start, &book),
+        [("d4".into(), 4), ("e4".into(), 3), ("Nf3".into(), 1)]
+    );
+
+    let after = {
+        let mut position = start.clone();
+        position.make_move(start.parse_san("e4").unwrap());
+        position
+    };
+
+    assert!(book.moves(&after).is_empty());
+}
+
+#[test]
+fn bytes_round_trip() {
+    let start = Position::start();
+    let castling = Position::from_fen(CASTLING).unwrap();
+    let mut book = Book::default();
+
+    book.add(&start, start.parse_san("e4").unwrap(), 5);
+    book.add(&castling, castling.parse_san("O-O").unwrap(), 1);
+    book.add(&castling, castling.parse_san("O-O-O").unwrap(), 2);
+
+    let bytes = book.to_bytes();
+    assert_eq!(bytes.len(), 3 * 16);
+
+    let read = Book::from_bytes(&bytes).unwrap();
+    assert_eq!(read, book);
+    assert_eq!(
+        san(&castling, &read),
+        [("O-O-O".into(), 2), ("O-O".into(), 1)]
+    );
+}
+
+#[test]
+fn castling_is_stored_as_king_takes_rook() {
+    let castling = Position::from_fen(CASTLING).unwrap();
+    let mut book = Book::default();
+    book.add(&castling, castling.parse_san("O-O").unwrap(), 1);
+
+    let bytes = book.to_bytes();
+    let raw = u16::from_be_bytes([bytes[8], bytes[9]]);
+
+    // e1 (4) to h1 (7)
+    assert_eq!(raw, 7 | 4 << 6);
+}
+
+#[test]
+fn selection() {
+    let start = Position::start();
+    let mut book = Book::default();
+
+    book.add(&start, start.parse_san("e4").unwrap(), 3);
+    book.add(&start, start.parse_san("d4").unwrap(), 1);
+
+    let e4 = start.parse_san("e4").unwrap();
+    let d4 = start.parse_san("d4").unwrap();
+
+    for random in 0..8 {
+        assert_eq!(book.choose_with(&start, Selection::Best, random), Some(e4));
+    }
+
+    let weighted: Vec<_> = (0..4)
+        .map(|random| book.choose_with(&start, Selection::Weighted, random))
+        .collect();
+
+    assert_eq!(weighted, [Some(e4), Some(e4), Some(e4), Some(d4)]);
+    assert_eq!(Book::default().choose(&start, Selection::Weighted), None);
+}
+
+#[test]
+fn truncated() {
+    assert!(matches!(
+        Book::from_bytes(&[0; 20]),
+        Err(BookError::Truncated { len: 20 })
+    ));
+
+    assert!(Book::from_bytes(&[]).unwrap().is_empty());
+}
@@ -0,0 +1,230 @@
+//! Time controls and clocks, run on a hand-driven time source.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use chess::clock::{Bonus, Stage, TimeSource};
+use chess::{Clock, DrawReason, Game, GameStatus, PieceColor, Position, TimeControl};
+
+use PieceColor::{Black, White};
+
+#[derive(Clone, Default)]
+struct Manual(Rc<Cell<Duration>>);
+
+impl Manual {
+    fn advance(&self, secs: f64) {
+        self.0.set(self.0.get() + Duration::from_secs_f64(secs));
+    }
+}
+
+impl TimeSource for Manual {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+}
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+fn clock(control: &str) -> (Clock<Manual>, Manual) {
+    let time = Manual::default();
+    let clock = Clock::with_source(control.parse().unwrap(), time.clone());
+
+    (clock, time)
+}
+
+#[test]
+fn parses_and_prints() {
+    let control: TimeControl = "40/90+30:30+30".parse().unwrap();
+
+    assert_eq!(
+        control.stages,
+        [
+            Stage {
+                moves: Some(40),
+                time: secs(90 * 60),
+                bonus: Bonus::Increment(secs(30)),
+            },
+            Stage {
+                moves: None,
+                time: secs(30 * 60),
+                bonus: Bonus::Increment(secs(30)),
+            },
+        ]
+    );
+
+    for text in ["5", "3+2", "0.5+1", "15d5", "15b5", "40/120:20/60:30"] {
+        assert_eq!(text.parse::<TimeControl>().unwrap().to_string(), text);
+    }
+
+    for text in [
+        "", "x", "40/", "0/90", "5+", "5+x", "-5", "5:", "inf", "NaN",
+    ] {
+        assert!(text.parse::<TimeControl>().is_err(), "{text}");
+    }
+
+    // Too long to count, in minutes or once made into seconds
+    for text in ["1e30", "40/1e20", "1e18", "5+1e30", "1e300:5"] {
+        assert!(text.parse::<TimeControl>().is_err(), "{text}");
+    }
+}
+
+#[test]
+fn sudden_death() {
+    let (mut clock, time) = clock("1");
+
+    time.advance(5.);
+    assert_eq!(clock.remaining(White), secs(60));
+
+    clock.start(White);
+    time.advance(10.);
+    assert_eq!(clock.remaining(White), secs(50));
+
+    clock.press();
+    assert_eq!(clock.active(), Some(Black));
+    time.advance(20.);
+    assert_eq!(clock.remaining(White), secs(50));
+    assert_eq!(clock.remaining(Black), secs(40));
+
+    time.advance(40.);
+    assert_eq!(clock.remaining(Black), Duration::ZERO);
+    assert_eq!(clock.flagged(), Some(Black));
+
+    // A move made after the flag fell does not save it
+    clock.press();
+    assert_eq!(clock.flagged(), Some(Black));
+    assert_eq!(clock.active(), None);
+}
+
+#[test]
+fn fischer_increment() {
+    let (mut clock, time) = clock("1+5");
+
+    clock.start(White);
+    time.advance(2.);
+    clock.press();
+
+    assert_eq!(clock.remaining(White), secs(63));
+}
+
+#[test]
+fn bronstein_delay() {
+    let (mut clock, time) = clock("1b5");
+
+    clock.start(White);
+    time.advance(2.);
+    clock.press();
+    assert_eq!(clock.remaining(White), secs(60));
+
+    time.advance(1.);
+    clock.press();
+    time.advance(8.);
+    clock.press();
+    assert_eq!(clock.remaining(White), secs(57));
+}
+
+#[test]
+fn simple_delay() {
+    let (mut clock, time) = clock("1d5");
+
+    clock.start(White);
+    time.advance(3.);
+    assert_eq!(clock.remaining(White), secs(60));
+
+    time.advance(4.);
+    assert_eq!(clock.remaining(White), secs(58));
+
+    clock.press();
+    assert_eq!(clock.remaining(White), secs(58));
+}
+
+#[test]
+fn stages() {
+    let (mut clock, time) = clock("2/1:1+10");
+
+    clock.start(White);
+    assert_eq!(clock.moves_to_go(White), Some(2));
+
+    for _ in 0..2 {
+        time.advance(10.);
+        clock.press();
+        time.advance(1.);
+        clock.press();
+    }
+
+    // The second move reaches the control: its time is added, but the
+    // second stage's increment only counts from the next move
+    assert_eq!(clock.remaining(White), secs(40 + 60));
+    assert_eq!(clock.remaining(Black), secs(58 + 60));
+    assert_eq!(clock.moves_to_go(White), None);
+
+    time.advance(10.);
+    clock.press();
+    assert_eq!(clock.remaining(White), secs(100));
+}
+
+#[test]
+fn repeating_last_stage() {
+    let (mut clock, time) = clock("1/1");
+
+    clock.start(White);
+    time.advance(30.);
+    clock.press();
+    clock.press();
+
+    assert_eq!(clock.remaining(White), secs(90));
+    assert_eq!(clock.moves_to_go(White), Some(1));
+}
+
+#[test]
+fn pause() {
+    let (mut clock, time) = clock("1");
+
+    clock.start(White);
+    time.advance(10.);
+    clock.pause();
+    time.advance(100.);
+
+    assert_eq!(clock.active(), None);
+    assert_eq!(clock.flagged(), None);
+    assert_eq!(clock.remaining(White), secs(50));
+}
+
+#[test]
+fn flag_fall() {
+    let mut game = Game::new();
+    game.flag(White);
+    assert_eq!(game.status(), GameStatus::TimeForfeit { winner: Black });
+
+    // A bare king cannot win, whatever the flagger has
+    let position = Position::from_fen("8/8/4k3/8/8/8/1K4Q1/8 w - - 0 1").unwrap();
+    let mut game = Game::from_position(position);
+    game.flag(White);
+    assert_eq!(
+        game.status(),
+        GameStatus::Draw(DrawReason::TimeoutVsInsufficientMaterial)
+    );
+
+    // A lone minor piece wins on time if the flagger has men of their own
+    // to box their king in, even a bishop on the other color
+    for fen in [
+        "8/8/4k3/8/8/3b4/1K3B2/8 w - - 0 1",
+        "8/8/4k3/8/8/3b4/1K4P1/8 w - - 0 1",
+        "8/8/4k3/8/8/3n4/1K4P1/8 w - - 0 1",
+        "8/8/4k3/8/8/3N4/1K4p1/8 b - - 0 1",
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        let flagger = position.side_to_move();
+        let mut game = Game::from_position(position);
+        game.flag(flagger);
+        assert_eq!(
+            game.status(),
+            GameStatus::TimeForfeit {
+                winner: flagger.opposite()
+            },
+            "{fen}"
+        );
+    }
+}
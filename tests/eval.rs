@@ -0,0 +1,79 @@
+//! Sanity checks of the static evaluation.
+
+use chess::eval::MAX_PHASE;
+use chess::{evaluate, Evaluation, Position};
+
+const POSITIONS: [&str; 5] = [
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "8/5k2/8/3P4/8/8/2K5/8 b - - 0 1",
+];
+
+/// The same position with the colors swapped and the board turned round.
+fn mirror(fen: &str) -> String {
+    let fields: Vec<&str> = fen.split(' ').collect();
+
+    let placement: Vec<String> = fields[0]
+        .split('/')
+        .rev()
+        .map(|rank| rank.chars().map(swap_case).collect())
+        .collect();
+
+    let side = if fields[1] == "w" { "b" } else { "w" };
+    let castling: String = fields[2].chars().map(swap_case).collect();
+
+    format!("{} {side} {castling} - 0 1", placement.join("/"))
+}
+
+fn swap_case(c: char) -> char {
+    if c.is_ascii_uppercase() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_ascii_uppercase()
+    }
+}
+
+fn eval(fen: &str) -> i32 {
+    evaluate(&Position::from_fen(fen).unwrap())
+}
+
+#[test]
+fn start_position_is_level() {
+    let eval = Evaluation::new(&Position::start());
+
+    assert_eq!(eval.total(), 0);
+    assert_eq!(eval.phase, MAX_PHASE);
+}
+
+#[test]
+fn colors_are_symmetric() {
+    for fen in POSITIONS {
+        assert_eq!(eval(fen), eval(&mirror(fen)), "{fen}");
+    }
+}
+
+#[test]
+fn extra_material_is_better() {
+    assert!(eval("4k3/8/8/8/8/8/8/R3K3 w - - 0 1") > 400);
+    assert!(eval("4k3/8/8/8/8/8/8/R3K3 b - - 0 1") < -400);
+}
+
+#[test]
+fn passed_pawns_count() {
+    let passed = Evaluation::new(&Position::from_fen("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1").unwrap());
+    let blocked =
+        Evaluation::new(&Position::from_fen("4k3/3p4/8/3P4/8/8/8/4K3 w - - 0 1").unwrap());
+
+    assert!(passed.pawns.eg > 0);
+    assert!(passed.pawns.eg > blocked.pawns.eg);
+}
+
+#[test]
+fn king_belongs_in_the_centre_late() {
+    let centre = eval("8/8/8/4k3/8/4K3/8/8 w - - 0 1");
+    let corner = eval("8/8/8/4k3/8/8/8/K7 w - - 0 1");
+
+    assert!(centre > corner);
+}
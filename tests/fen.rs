@@ -0,0 +1,213 @@
+//! Writing positions out as FEN and reading them back, and the FEN strings
+//! the parser must reject, with where it says the fault is.
+
+use chess::consts::START_FEN;
+use chess::{FenError, PieceColor, Position};
+
+fn err(fen: &str) -> FenError {
+    Position::from_fen(fen).unwrap_err()
+}
+
+#[test]
+fn placement() {
+    assert_eq!(
+        err("rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        FenError::InvalidPiece { ch: 'x', index: 13 }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/8/8/8/4K3 w - - 0 1"),
+        FenError::WrongRankCount {
+            found: 7,
+            index: 17
+        }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K3/8 w - - 0 1"),
+        FenError::WrongRankCount {
+            found: 9,
+            index: 19
+        }
+    );
+
+    assert_eq!(
+        err("4k3/9/8/8/8/8/8/4K3 w - - 0 1"),
+        FenError::RankTooLong { index: 4 }
+    );
+
+    assert_eq!(
+        err("4k3/ppppppppp/8/8/8/8/8/4K3 w - - 0 1"),
+        FenError::RankTooLong { index: 12 }
+    );
+
+    assert_eq!(
+        err("4k3/7/8/8/8/8/8/4K3 w - - 0 1"),
+        FenError::RankTooShort { index: 5 }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K2 w - - 0 1"),
+        FenError::RankTooShort { index: 19 }
+    );
+}
+
+#[test]
+fn kings_and_pawns() {
+    assert_eq!(
+        err("8/8/8/8/8/8/8/4K3 w - - 0 1"),
+        FenError::MissingKing {
+            color: PieceColor::Black
+        }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"),
+        FenError::TooManyKings {
+            color: PieceColor::White,
+            index: 18
+        }
+    );
+
+    assert_eq!(
+        err("4k2P/8/8/8/8/8/8/4K3 w - - 0 1"),
+        FenError::PawnOnBackRank { index: 3 }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/p3K3 w - - 0 1"),
+        FenError::PawnOnBackRank { index: 16 }
+    );
+}
+
+#[test]
+fn side_and_castling() {
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+        FenError::InvalidSideToMove { index: 20 }
+    );
+
+    // Twice the same right, and a right with no rook behind it
+    assert_eq!(
+        err("r3k2r/8/8/8/8/8/8/R3K2R w KKq - 0 1"),
+        FenError::InvalidCastling { index: 26 }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K3 w K - 0 1"),
+        FenError::InvalidCastling { index: 22 }
+    );
+}
+
+#[test]
+fn en_passant() {
+    // Off the board, and on the wrong rank for the side to move
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K3 w - e9 0 1"),
+        FenError::InvalidEnPassant { index: 24 }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/3Pp3/8/8/8/4K3 w - e3 0 1"),
+        FenError::InvalidEnPassant { index: 27 }
+    );
+
+    // No pawn past the square; this one used to parse and then crash
+    // generating moves
+    assert_eq!(
+        err("4k3/8/8/4P3/8/8/8/4K3 w - e6 0 1"),
+        FenError::InvalidEnPassant { index: 26 }
+    );
+
+    // A piece of the side to move where the pawn should be
+    assert_eq!(
+        err("4k3/8/8/3PN3/8/8/8/4K3 w - e6 0 1"),
+        FenError::InvalidEnPassant { index: 27 }
+    );
+
+    // The square itself taken, or the pawn's start square
+    assert_eq!(
+        err("4k3/8/4n3/3Pp3/8/8/8/4K3 w - e6 0 1"),
+        FenError::InvalidEnPassant { index: 29 }
+    );
+
+    assert_eq!(
+        err("4k3/4n3/8/3Pp3/8/8/8/4K3 w - e6 0 1"),
+        FenError::InvalidEnPassant { index: 29 }
+    );
+
+    let position = Position::from_fen("4k3/8/8/3Pp3/8/8/8/4K3 w - e6 0 1").unwrap();
+    let capture = position.parse_uci("d5e6").unwrap();
+    assert!(position.legal_moves().contains(&capture));
+
+    let position =
+        Position::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    assert_eq!(position.to_fen().split(' ').nth(3), Some("e3"));
+}
+
+#[test]
+fn counters_and_extra_fields() {
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+        FenError::InvalidHalfmoveClock { index: 26 }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K3 w - - 0 -1"),
+        FenError::InvalidFullmoveNumber { index: 28 }
+    );
+
+    assert_eq!(
+        err("4k3/8/8/8/8/8/8/4K3 w - - 0 1 extra"),
+        FenError::UnexpectedField { index: 30 }
+    );
+}
+
+#[test]
+fn errors_leave_the_position_alone() {
+    let mut position = Position::start();
+
+    assert!(position
+        .parse_fen("4k3/8/8/4P3/8/8/8/4K3 w - e6 0 1")
+        .is_err());
+    assert_eq!(position, Position::start());
+}
+
+#[test]
+fn round_trip() {
+    // The perft reference positions
+    for fen in [
+        START_FEN,
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    ] {
+        let position = Position::from_fen(fen).unwrap();
+        assert_eq!(position.to_fen(), fen);
+    }
+
+    // Positions reached by play: after a double push, an en passant
+    // capture and castling on both sides
+    let mut position = Position::start();
+
+    for uci in [
+        "e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "g8f6", "g1f3", "c8e6", "f1e2", "d8d6", "e1g1",
+        "b8c6", "d2d4", "e8c8",
+    ] {
+        let mv = position.parse_uci(uci).unwrap();
+        position.make_move(mv);
+
+        let fen = position.to_fen();
+        let parsed = Position::from_fen(&fen).unwrap();
+        assert_eq!(parsed, position, "{fen}");
+        assert_eq!(parsed.to_fen(), fen);
+    }
+
+    assert_eq!(
+        position.to_fen(),
+        "2kr1b1r/ppp1p1pp/2nqbn2/3p4/3P4/5N2/PPP1BPPP/RNBQ1RK1 w - - 1 8"
+    );
+}
@@ -0,0 +1,222 @@
+//! Taking moves back and replaying them, and how games end.
+
+use chess::{DrawReason, Game, GameStatus, PieceColor, Position};
+
+/// Plays `moves`, given in SAN, from the start.
+fn play(moves: &[&str]) -> Game {
+    let mut game = Game::new();
+
+    for san in moves {
+        let mv = game.position().parse_san(san).unwrap();
+        game.play(mv).unwrap();
+    }
+
+    game
+}
+
+/// Plays `moves`, given in SAN, from `fen`.
+fn play_from(fen: &str, moves: &[&str]) -> Game {
+    let mut game = Game::from_position(Position::from_fen(fen).unwrap());
+
+    for san in moves {
+        let mv = game.position().parse_san(san).unwrap();
+        game.play(mv).unwrap();
+    }
+
+    game
+}
+
+fn status(fen: &str) -> GameStatus {
+    play_from(fen, &[]).status()
+}
+
+#[test]
+fn undo_restores_every_position_exactly() {
+    // A capture en passant, castling on both sides and a plain capture
+    let moves = [
+        "e4", "Nf6", "e5", "d5", "exd6", "Nc6", "Nf3", "Bf5", "Bc4", "Qd7", "O-O", "O-O-O", "dxc7",
+    ];
+
+    let mut game = Game::new();
+    let mut fens = vec![game.position().to_fen()];
+
+    for san in moves {
+        let mv = game.position().parse_san(san).unwrap();
+        game.play(mv).unwrap();
+        fens.push(game.position().to_fen());
+    }
+
+    while game.undo().is_some() {
+        fens.pop();
+        assert_eq!(game.position().to_fen(), *fens.last().unwrap());
+    }
+
+    assert_eq!(*game.position(), Position::start());
+    assert!(game.moves().is_empty());
+
+    while game.redo().is_some() {}
+
+    assert_eq!(game, play(&moves));
+}
+
+#[test]
+fn redo_history() {
+    let mut game = play(&["e4", "e5", "Nf3", "Nc6"]);
+
+    assert!(game.undo().is_some());
+    assert!(game.undo().is_some());
+    assert_eq!(game.undone().count(), 2);
+
+    // Replaying the move taken back keeps the rest to redo
+    let nf3 = game.position().parse_san("Nf3").unwrap();
+    game.play(nf3).unwrap();
+    assert_eq!(game.undone().count(), 1);
+
+    assert!(game.undo().is_some());
+    let bc4 = game.position().parse_san("Bc4").unwrap();
+    game.play(bc4).unwrap();
+    assert_eq!(game.undone().count(), 0);
+    assert_eq!(game.redo(), None);
+}
+
+#[test]
+fn go_to() {
+    let mut game = play(&["d4", "d5", "c4", "e6", "Nc3"]);
+    let end = game.clone();
+
+    game.go_to(1);
+    assert_eq!(*game.position(), end.positions()[1]);
+
+    game.go_to(usize::MAX);
+    assert_eq!(game, end);
+
+    game.go_to(0);
+    game.go_to(3);
+    assert_eq!(game.moves(), &end.moves()[..3]);
+}
+
+#[test]
+fn undo_reopens_the_game() {
+    let mut game = play(&["f3", "e5", "g4", "Qh4#"]);
+    assert_eq!(
+        game.status(),
+        GameStatus::Checkmate {
+            winner: PieceColor::Black
+        }
+    );
+
+    game.undo();
+    assert_eq!(game.status(), GameStatus::InProgress);
+
+    game.resign(PieceColor::Black);
+    assert_eq!(game.redo(), None);
+
+    game.undo();
+    assert_eq!(game.status(), GameStatus::InProgress);
+}
+
+#[test]
+fn checkmate_and_stalemate() {
+    let game = play_from("7k/8/6K1/8/8/8/8/Q7 w - - 0 1", &["Qa8"]);
+    assert_eq!(
+        game.status(),
+        GameStatus::Checkmate {
+            winner: PieceColor::White
+        }
+    );
+    assert_eq!(game.status().winner(), Some(PieceColor::White));
+
+    let game = play_from("7k/8/6K1/8/8/8/5Q2/8 w - - 0 1", &["Qf7"]);
+    assert_eq!(game.status(), GameStatus::Stalemate);
+    assert_eq!(game.status().winner(), None);
+}
+
+#[test]
+fn repetition() {
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+    // The start position for the third time: a draw to claim, not one
+    // that ends the game
+    let mut game = play(&shuffle.repeat(2));
+    assert_eq!(game.repetitions(), 3);
+    assert_eq!(game.status(), GameStatus::InProgress);
+    assert_eq!(game.claimable_draw(), Some(DrawReason::ThreefoldRepetition));
+
+    let mut claimed = game.clone();
+    assert!(claimed.claim_draw());
+    assert_eq!(
+        claimed.status(),
+        GameStatus::Draw(DrawReason::ThreefoldRepetition)
+    );
+
+    // The fifth time ends it on its own
+    for san in shuffle.repeat(2) {
+        assert_eq!(game.status(), GameStatus::InProgress);
+
+        let mv = game.position().parse_san(san).unwrap();
+        game.play(mv).unwrap();
+    }
+
+    assert_eq!(game.repetitions(), 5);
+    assert_eq!(
+        game.status(),
+        GameStatus::Draw(DrawReason::FivefoldRepetition)
+    );
+    assert_eq!(game.claimable_draw(), None);
+}
+
+#[test]
+fn move_rules() {
+    let game = play_from("4k3/8/8/8/8/8/R7/4K3 w - - 98 80", &["Ra3"]);
+    assert_eq!(game.claimable_draw(), None);
+
+    // Fifty moves each without a capture or pawn move may be claimed...
+    let mut game = play_from("4k3/8/8/8/8/8/R7/4K3 w - - 99 80", &["Ra3"]);
+    assert_eq!(game.status(), GameStatus::InProgress);
+    assert_eq!(game.claimable_draw(), Some(DrawReason::FiftyMoveRule));
+    assert!(game.claim_draw());
+    assert_eq!(game.status(), GameStatus::Draw(DrawReason::FiftyMoveRule));
+
+    // ...seventy-five end the game
+    let game = play_from("4k3/8/8/8/8/8/R7/4K3 w - - 149 105", &["Ra3"]);
+    assert_eq!(
+        game.status(),
+        GameStatus::Draw(DrawReason::SeventyFiveMoveRule)
+    );
+
+    // Unless that last move mates
+    let game = play_from("7k/8/6K1/8/8/8/8/Q7 w - - 149 105", &["Qa8"]);
+    assert_eq!(
+        game.status(),
+        GameStatus::Checkmate {
+            winner: PieceColor::White
+        }
+    );
+}
+
+#[test]
+fn insufficient_material() {
+    let draw = GameStatus::Draw(DrawReason::InsufficientMaterial);
+
+    assert_eq!(status("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), draw);
+    assert_eq!(status("4k3/8/8/8/8/8/8/4K1N1 w - - 0 1"), draw);
+    assert_eq!(status("4k3/8/8/8/8/8/8/4Kb2 w - - 0 1"), draw);
+
+    // Bishops on squares of one color, however many and whoever's
+    assert_eq!(status("4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1"), draw);
+    assert_eq!(status("4k3/8/8/8/8/8/3B4/2B1K3 w - - 0 1"), draw);
+
+    // Reached by capturing the last piece that could mate
+    let game = play_from("4k3/8/8/8/8/8/8/3rK1N1 w - - 0 1", &["Kxd1"]);
+    assert_eq!(game.status(), draw);
+
+    // Bishops on opposite colors can still mate, with help
+    assert_eq!(
+        status("2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1"),
+        GameStatus::InProgress
+    );
+    assert_eq!(
+        status("4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1"),
+        GameStatus::InProgress
+    );
+}
@@ -0,0 +1,69 @@
+//! The incrementally updated Zobrist hash against one computed from
+//! scratch.
+
+use chess::consts::START_FEN;
+use chess::Position;
+
+const POSITIONS: [&str; 5] = [
+    START_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+];
+
+/// Walks every line `depth` plies deep, checking the hash after each move
+/// against the position rebuilt from its FEN, and after each unmake
+/// against the hash before the move.
+fn walk(position: &mut Position, depth: u32) {
+    let hash = position.hash();
+    let rebuilt = Position::from_fen(&position.to_fen()).unwrap();
+
+    assert_eq!(hash, rebuilt.hash(), "{}", position.to_fen());
+
+    if depth == 0 {
+        return;
+    }
+
+    for mv in position.legal_moves() {
+        let undo = position.make_move(mv);
+        walk(position, depth - 1);
+        position.unmake_move(mv, undo);
+
+        assert_eq!(position.hash(), hash, "unmaking {mv}");
+    }
+}
+
+#[test]
+fn incremental_hash_matches_a_rebuild() {
+    for fen in POSITIONS {
+        walk(&mut Position::from_fen(fen).unwrap(), 3);
+    }
+}
+
+#[test]
+fn transpositions_hash_alike() {
+    let play = |moves: &[&str]| {
+        let mut position = Position::start();
+
+        for uci in moves {
+            let mv = position.parse_uci(uci).unwrap();
+            position.make_move(mv);
+        }
+
+        position.hash()
+    };
+
+    assert_eq!(
+        play(&["g1f3", "g8f6", "b1c3", "b8c6"]),
+        play(&["b1c3", "b8c6", "g1f3", "g8f6"])
+    );
+
+    // Back to the start, and to its placement without the right to castle
+    // kingside
+    assert_eq!(play(&["g1f3", "g8f6", "f3g1", "f6g8"]), play(&[]));
+    assert_ne!(
+        play(&["g1f3", "g8f6", "h1g1", "h8g8", "g1h1", "g8h8", "f3g1", "f6g8"]),
+        play(&[])
+    );
+}
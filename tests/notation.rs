@@ -0,0 +1,88 @@
+//! SAN and UCI formatting and parsing.
+
+use chess::{Move, ParseMoveError, Piece, Position, Square};
+
+fn sq(s: &str) -> Square {
+    Square::from_algebraic(s).unwrap()
+}
+
+fn san(fen: &str, from: &str, to: &str) -> String {
+    let position = Position::from_fen(fen).unwrap();
+    Move::new(sq(from), sq(to)).to_san(&position)
+}
+
+#[test]
+fn disambiguation() {
+    // Knights on b1 and f1 both reach d2: the file tells them apart
+    assert_eq!(san("k7/8/8/8/8/8/8/KN3N2 w - - 0 1", "b1", "d2"), "Nbd2");
+
+    // Rooks on a1 and a5 share a file: the rank tells them apart
+    assert_eq!(san("7k/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1", "a3"), "R1a3");
+
+    // Queens on a1, c1 and a3 all reach b2: only the full square will do
+    assert_eq!(san("6k1/8/8/8/8/Q7/8/Q1Q4K w - - 0 1", "a1", "b2"), "Qa1b2");
+
+    // A pinned rival needs no disambiguation
+    assert_eq!(san("k7/8/8/8/b7/8/2N5/3K1N2 w - - 0 1", "f1", "e3"), "Ne3");
+}
+
+#[test]
+fn captures_checks_and_mates() {
+    assert_eq!(san("k7/8/8/3p4/4P3/8/8/K7 w - - 0 1", "e4", "d5"), "exd5");
+    assert_eq!(san("k7/8/8/3pP3/8/8/8/K7 w - d6 0 1", "e5", "d6"), "exd6");
+    assert_eq!(san("k7/8/8/8/8/8/8/K6R w - - 0 1", "h1", "h8"), "Rh8+");
+    assert_eq!(san("k7/8/1K6/8/8/8/8/7R w - - 0 1", "h1", "h8"), "Rh8#");
+    assert_eq!(san("r3k3/8/8/8/8/8/8/4K3 b q - 0 1", "e8", "c8"), "O-O-O");
+}
+
+#[test]
+fn promotions() {
+    let position = Position::from_fen("1n5k/P7/8/8/8/8/8/K7 w - - 0 1").unwrap();
+
+    let push = Move::with_promotion(sq("a7"), sq("a8"), Piece::Queen);
+    let take = Move::with_promotion(sq("a7"), sq("b8"), Piece::Knight);
+
+    assert_eq!(push.to_san(&position), "a8=Q");
+    assert_eq!(take.to_san(&position), "axb8=N");
+    assert_eq!(push.to_uci(), "a7a8q");
+
+    assert_eq!(position.parse_san("a8=Q"), Ok(push));
+    assert_eq!(position.parse_san("a8Q"), Ok(push));
+    assert_eq!(position.parse_uci("a7b8n"), Ok(take));
+    assert_eq!(position.parse_san("a8"), Err(ParseMoveError::Illegal));
+}
+
+#[test]
+fn parse_errors() {
+    let position = Position::from_fen("k7/8/8/8/8/8/8/KN3N2 w - - 0 1").unwrap();
+
+    assert_eq!(position.parse_san("Nd2"), Err(ParseMoveError::Ambiguous));
+    assert_eq!(position.parse_san("Nd4"), Err(ParseMoveError::Illegal));
+    assert_eq!(position.parse_san("Zd2"), Err(ParseMoveError::Invalid));
+    assert_eq!(position.parse_san("e4e5"), Err(ParseMoveError::Invalid));
+
+    assert_eq!(position.parse_uci("b1d3"), Err(ParseMoveError::Illegal));
+    assert_eq!(position.parse_uci("b1d2x"), Err(ParseMoveError::Invalid));
+    assert_eq!(position.parse_uci("i1d2"), Err(ParseMoveError::Invalid));
+}
+
+/// Every legal move in a few busy positions survives a trip through both
+/// notations.
+#[test]
+fn round_trips() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+
+    for fen in fens {
+        let position = Position::from_fen(fen).unwrap();
+
+        for mv in position.legal_moves() {
+            assert_eq!(position.parse_san(&mv.to_san(&position)), Ok(mv), "{fen}");
+            assert_eq!(position.parse_uci(&mv.to_uci()), Ok(mv), "{fen}");
+        }
+    }
+}
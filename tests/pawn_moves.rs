@@ -0,0 +1,212 @@
+//! Per-square checks of the pawn move generator for both colors.
+
+use chess::{Move, Piece, PieceColor, Position, Square};
+
+const COLORS: [PieceColor; 2] = [PieceColor::White, PieceColor::Black];
+
+fn forward(color: PieceColor) -> i32 {
+    match color {
+        PieceColor::White => 1,
+        PieceColor::Black => -1,
+    }
+}
+
+fn start_rank(color: PieceColor) -> u8 {
+    match color {
+        PieceColor::White => 1,
+        PieceColor::Black => 6,
+    }
+}
+
+/// Every square a pawn of `color` can legally stand on.
+fn pawn_squares() -> impl Iterator<Item = Square> {
+    Square::all().filter(|sq| (1..=6).contains(&sq.rank()))
+}
+
+/// The moves a pawn of `from` to `to` expands to, promotions included.
+fn expand(from: Square, to: Square) -> Vec<Move> {
+    if to.rank() == 0 || to.rank() == 7 {
+        Piece::PROMOTIONS
+            .iter()
+            .map(|&piece| Move::with_promotion(from, to, piece))
+            .collect()
+    } else {
+        vec![Move::new(from, to)]
+    }
+}
+
+fn sorted(mut moves: Vec<Move>) -> Vec<Move> {
+    moves.sort_by_key(|mv| (mv.from, mv.to, mv.promotion.map(|p| p as u8)));
+    moves
+}
+
+fn pawn_position(square: Square, color: PieceColor) -> Position {
+    let mut position = Position::empty();
+    position.set_piece(square, Some((Piece::Pawn, color)));
+    position
+}
+
+#[test]
+fn pushes_from_every_square() {
+    for color in COLORS {
+        for from in pawn_squares() {
+            let position = pawn_position(from, color);
+
+            let mut expected = expand(from, from.offset(0, forward(color)).unwrap());
+            if from.rank() == start_rank(color) {
+                expected.extend(expand(from, from.offset(0, 2 * forward(color)).unwrap()));
+            }
+
+            assert_eq!(
+                sorted(position.calc_moves(from)),
+                sorted(expected),
+                "{color:?} pawn on {from}"
+            );
+        }
+    }
+}
+
+#[test]
+fn blocked_pawns_do_not_push() {
+    for color in COLORS {
+        for blocker in COLORS {
+            for from in pawn_squares() {
+                let mut position = pawn_position(from, color);
+                let one = from.offset(0, forward(color)).unwrap();
+                position.set_piece(one, Some((Piece::Knight, blocker)));
+
+                assert!(
+                    position.calc_moves(from).is_empty(),
+                    "{color:?} pawn on {from} blocked by {blocker:?}"
+                );
+
+                // Only the double push is blocked
+                if from.rank() == start_rank(color) {
+                    let mut position = pawn_position(from, color);
+                    let two = from.offset(0, 2 * forward(color)).unwrap();
+                    position.set_piece(two, Some((Piece::Knight, blocker)));
+
+                    assert_eq!(position.calc_moves(from), vec![Move::new(from, one)]);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn captures_on_both_diagonals_from_every_square() {
+    for color in COLORS {
+        for from in pawn_squares() {
+            let mut position = pawn_position(from, color);
+            let mut expected = vec![];
+
+            // Block the pushes so only captures are left
+            let one = from.offset(0, forward(color)).unwrap();
+            position.set_piece(one, Some((Piece::Knight, color)));
+
+            for df in [-1, 1] {
+                if let Some(to) = from.offset(df, forward(color)) {
+                    position.set_piece(to, Some((Piece::Knight, color.opposite())));
+                    expected.extend(expand(from, to));
+                }
+            }
+
+            assert_eq!(
+                sorted(position.calc_moves(from)),
+                sorted(expected),
+                "{color:?} pawn on {from}"
+            );
+        }
+    }
+}
+
+#[test]
+fn does_not_capture_own_pieces() {
+    for color in COLORS {
+        for from in pawn_squares() {
+            let mut position = pawn_position(from, color);
+            let one = from.offset(0, forward(color)).unwrap();
+            position.set_piece(one, Some((Piece::Knight, color)));
+
+            for df in [-1, 1] {
+                if let Some(to) = from.offset(df, forward(color)) {
+                    position.set_piece(to, Some((Piece::Knight, color)));
+                }
+            }
+
+            assert!(
+                position.calc_moves(from).is_empty(),
+                "{color:?} pawn on {from}"
+            );
+        }
+    }
+}
+
+#[test]
+fn en_passant_from_every_file() {
+    for color in COLORS {
+        // The rank the capturing pawn stands on
+        let rank = match color {
+            PieceColor::White => 4,
+            PieceColor::Black => 3,
+        };
+
+        for file in 0..8 {
+            for df in [-1, 1] {
+                let Some(from) = Square::new(file, rank).offset(df, 0) else {
+                    continue;
+                };
+
+                let enemy = color.opposite();
+                let enemy_start = Square::new(file, start_rank(enemy));
+                let enemy_to = Square::new(file, rank);
+                let target = Square::new(file, (start_rank(enemy) + rank) / 2);
+
+                let mut position = pawn_position(from, color);
+                position.set_piece(enemy_start, Some((Piece::Pawn, enemy)));
+                position.make_move(Move::new(enemy_start, enemy_to));
+
+                assert_eq!(position.en_passant(), Some(target));
+
+                let capture = Move::new(from, target);
+                assert!(
+                    position.calc_moves(from).contains(&capture),
+                    "{color:?} pawn on {from} taking on {target}"
+                );
+
+                position.make_move(capture);
+                assert_eq!(position.piece_at(enemy_to), None);
+                assert_eq!(position.piece_at(target), Some((Piece::Pawn, color)));
+            }
+        }
+    }
+}
+
+#[test]
+fn en_passant_expires_after_one_move() {
+    let sq = |s| Square::from_algebraic(s).unwrap();
+
+    let mut position = Position::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    assert!(position
+        .calc_moves(sq("e5"))
+        .contains(&Move::new(sq("e5"), sq("d6"))));
+
+    position.make_move(Move::new(sq("e1"), sq("e2")));
+    position.make_move(Move::new(sq("e8"), sq("e7")));
+
+    assert_eq!(position.en_passant(), None);
+    assert!(!position
+        .calc_moves(sq("e5"))
+        .contains(&Move::new(sq("e5"), sq("d6"))));
+}
+
+#[test]
+fn start_position_pawn_moves() {
+    let position = Position::start();
+
+    for from in Square::all().filter(|sq| sq.rank() == 1 || sq.rank() == 6) {
+        assert_eq!(position.calc_moves(from).len(), 2, "pawn on {from}");
+    }
+
+    assert_eq!(position.legal_moves().len(), 20);
+}
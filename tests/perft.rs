@@ -0,0 +1,121 @@
+//! Node counts for the well-known perft positions, from
+//! <https://www.chessprogramming.org/Perft_Results>.
+//!
+//! The default depths keep `cargo test` quick in debug builds. The deeper
+//! counts are ignored; run them with `cargo test --release -- --ignored`.
+
+use chess::consts::START_FEN;
+use chess::Position;
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_4_MIRRORED: &str =
+    "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
+/// Checks `expected[i]` against perft at depth `i + 1`.
+fn check(fen: &str, expected: &[u64]) {
+    let position = Position::from_fen(fen).unwrap();
+
+    for (depth, &nodes) in (1..).zip(expected) {
+        assert_eq!(position.perft(depth), nodes, "{fen} at depth {depth}");
+    }
+}
+
+#[test]
+fn start_position() {
+    check(START_FEN, &[20, 400, 8_902, 197_281]);
+}
+
+#[test]
+fn kiwipete() {
+    check(KIWIPETE, &[48, 2_039, 97_862]);
+}
+
+#[test]
+fn position_3() {
+    check(POSITION_3, &[14, 191, 2_812, 43_238]);
+}
+
+#[test]
+fn position_4() {
+    check(POSITION_4, &[6, 264, 9_467]);
+}
+
+#[test]
+fn position_4_mirrored() {
+    check(POSITION_4_MIRRORED, &[6, 264, 9_467]);
+}
+
+#[test]
+fn position_5() {
+    check(POSITION_5, &[44, 1_486, 62_379]);
+}
+
+#[test]
+fn position_6() {
+    check(POSITION_6, &[46, 2_079, 89_890]);
+}
+
+#[test]
+fn depth_zero_is_one_node() {
+    assert_eq!(Position::start().perft(0), 1);
+    assert!(Position::start().divide(0).is_empty());
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_chess"))
+        .args(["perft", "startpos", "0"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .ends_with("Nodes searched: 1\n"));
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let position = Position::from_fen(KIWIPETE).unwrap();
+    let divide = position.divide(2);
+
+    assert_eq!(divide.len(), 48);
+    assert_eq!(divide.iter().map(|(_, n)| n).sum::<u64>(), 2_039);
+}
+
+#[test]
+#[ignore]
+fn start_position_deep() {
+    check(START_FEN, &[20, 400, 8_902, 197_281, 4_865_609]);
+}
+
+#[test]
+#[ignore]
+fn kiwipete_deep() {
+    check(KIWIPETE, &[48, 2_039, 97_862, 4_085_603]);
+}
+
+#[test]
+#[ignore]
+fn position_3_deep() {
+    check(POSITION_3, &[14, 191, 2_812, 43_238, 674_624, 11_030_083]);
+}
+
+#[test]
+#[ignore]
+fn position_4_deep() {
+    check(POSITION_4, &[6, 264, 9_467, 422_333, 15_833_292]);
+}
+
+#[test]
+#[ignore]
+fn position_5_deep() {
+    check(POSITION_5, &[44, 1_486, 62_379, 2_103_487]);
+}
+
+#[test]
+#[ignore]
+fn position_6_deep() {
+    check(POSITION_6, &[46, 2_079, 89_890, 3_894_594]);
+}
@@ -0,0 +1,208 @@
+//! PGN export of played games and import of annotated game files.
+
+use chess::{parse_pgn, Branch, Game, GameStatus, PgnErrorKind, PieceColor, Position, Tags};
+
+const ANNOTATED: &str = r#"
+[Event "Club night"]
+[White "Anderssen"]
+[Black "Kieseritzky"]
+[Result "1-0"]
+
+{A quiet start} 1. e4 e5!? 2. Nf3 (2. Bc4 {Bishop's opening} Nc6 (2... Nf6 3. d3) 3. Qh5)
+2... Nc6 $1 ; a rest-of-line comment
+3.Bb5 a6 1-0
+
+[Event "Second"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1"]
+
+1. O-O-O Kf7 2. Rd7+ 1/2-1/2
+"#;
+
+fn play(game: &mut Game, moves: &[&str]) {
+    for san in moves {
+        let mv = game.position().parse_san(san).unwrap();
+        game.play(mv).unwrap();
+    }
+}
+
+#[test]
+fn export_has_roster_and_result() {
+    let mut game = Game::new();
+    play(&mut game, &["f3", "e5", "g4", "Qh4#"]);
+
+    let pgn = game.to_pgn(&Tags::default());
+
+    assert!(pgn.starts_with(
+        "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+         [White \"?\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n"
+    ));
+    assert!(pgn.ends_with("1. f3 e5 2. g4 Qh4# 0-1\n\n"));
+    assert!(!pgn.contains("FEN"));
+}
+
+#[test]
+fn export_custom_start() {
+    let position = Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 7").unwrap();
+    let mut game = Game::from_position(position);
+    play(&mut game, &["O-O-O", "Ke2"]);
+
+    let pgn = game.to_pgn(&Tags::default());
+
+    assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 7\"]\n"));
+    assert!(pgn.ends_with("7... O-O-O 8. Ke2 *\n\n"));
+}
+
+#[test]
+fn export_round_trips() {
+    let mut game = Game::new();
+    play(
+        &mut game,
+        &[
+            "e4", "d5", "exd5", "Qxd5", "Nc3", "Qa5", "d4", "c6", "Nf3", "Bg4",
+        ],
+    );
+
+    let games = parse_pgn(&game.to_pgn(&Tags::default())).unwrap();
+
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].to_game(), game);
+}
+
+#[test]
+fn import_tags_comments_and_nags() {
+    let games = parse_pgn(ANNOTATED).unwrap();
+    assert_eq!(games.len(), 2);
+
+    let game = &games[0];
+    assert_eq!(game.tag("White"), Some("Anderssen"));
+    assert_eq!(game.result, "1-0");
+    assert_eq!(game.moves.len(), 6);
+    assert_eq!(
+        game.moves[0].comment_before.as_deref(),
+        Some("A quiet start")
+    );
+    assert_eq!(game.moves[1].nags, [5]);
+    assert_eq!(game.moves[3].nags, [1]);
+
+    let second = &games[1];
+    assert_eq!(
+        second.start,
+        Position::from_fen(second.tag("FEN").unwrap()).unwrap()
+    );
+    assert_eq!(second.moves.len(), 3);
+}
+
+#[test]
+fn result_tags() {
+    // The file only says who won, not how
+    let games = parse_pgn(ANNOTATED).unwrap();
+    assert_eq!(
+        games[0].to_game().status(),
+        GameStatus::Recorded {
+            winner: Some(PieceColor::White)
+        }
+    );
+    assert_eq!(
+        games[1].to_game().status(),
+        GameStatus::Recorded { winner: None }
+    );
+
+    // A game the moves already ended keeps its own ending
+    let games = parse_pgn("1. f3 e5 2. g4 Qh4# 0-1").unwrap();
+    assert_eq!(
+        games[0].to_game().status(),
+        GameStatus::Checkmate {
+            winner: PieceColor::Black
+        }
+    );
+
+    let game = games[0].to_game();
+    assert!(game.to_pgn(&Tags::default()).ends_with("Qh4# 0-1\n\n"));
+
+    let games = parse_pgn("1. e4 e5 *").unwrap();
+    assert_eq!(games[0].to_game().status(), GameStatus::InProgress);
+}
+
+#[test]
+fn import_nested_variations() {
+    let games = parse_pgn(ANNOTATED).unwrap();
+    let variations = &games[0].moves[2].variations;
+
+    assert_eq!(variations.len(), 1);
+
+    let line = &variations[0];
+    assert_eq!(line.len(), 3);
+    assert_eq!(line[0].comment.as_deref(), Some("Bishop's opening"));
+    assert_eq!(line[1].variations.len(), 1);
+    assert_eq!(line[1].variations[0].len(), 2);
+}
+
+#[test]
+fn stepping_into_variations() {
+    let games = parse_pgn(ANNOTATED).unwrap();
+    let game = &games[0];
+
+    let sans = |path: &[Branch]| -> Option<Vec<String>> {
+        let mut position = game.start.clone();
+        let line = game.line(path)?;
+
+        Some(
+            line.iter()
+                .map(|pgn| {
+                    let san = pgn.mv.to_san(&position);
+                    position.make_move(pgn.mv);
+                    san
+                })
+                .collect(),
+        )
+    };
+
+    let bishops_opening = Branch { ply: 2, index: 0 };
+    let knight_defence = Branch { ply: 3, index: 0 };
+
+    assert_eq!(sans(&[]).unwrap(), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+    assert_eq!(
+        sans(&[bishops_opening]).unwrap(),
+        ["e4", "e5", "Bc4", "Nc6", "Qh5"]
+    );
+    assert_eq!(
+        sans(&[bishops_opening, knight_defence]).unwrap(),
+        ["e4", "e5", "Bc4", "Nf6", "d3"]
+    );
+
+    // Branches that are not there, or that go back before the last one
+    assert_eq!(sans(&[Branch { ply: 2, index: 1 }]), None);
+    assert_eq!(sans(&[Branch { ply: 1, index: 0 }]), None);
+    assert_eq!(sans(&[knight_defence, bishops_opening]), None);
+
+    // Only the line actually played has the result
+    let variation = game.variation(&[bishops_opening]).unwrap();
+    assert_eq!(variation.moves().len(), 5);
+    assert_eq!(variation.status(), GameStatus::InProgress);
+}
+
+#[test]
+fn illegal_move_reports_game_and_ply() {
+    let pgn = "1. e4 e5 *\n\n1. e4 e5 2. Nf3 Nc6 3. Ke3 *";
+    let err = parse_pgn(pgn).unwrap_err();
+
+    assert_eq!((err.game, err.ply), (2, 5));
+    assert_eq!(err.kind, PgnErrorKind::IllegalMove("Ke3".into()));
+}
+
+#[test]
+fn ambiguous_move_is_rejected() {
+    let pgn = "[FEN \"k7/8/8/8/8/8/1N3N2/K7 w - - 0 1\"]\n\n1. Nd3 *";
+    let err = parse_pgn(pgn).unwrap_err();
+
+    assert_eq!((err.game, err.ply), (1, 1));
+    assert_eq!(err.kind, PgnErrorKind::AmbiguousMove("Nd3".into()));
+}
+
+#[test]
+fn unterminated_variation_is_rejected() {
+    let err = parse_pgn("1. e4 (1. d4 d5 *").unwrap_err();
+
+    assert_eq!(err.kind, PgnErrorKind::UnterminatedVariation);
+}
@@ -0,0 +1,117 @@
+//! Tactical checks of the search: it must find short mates, win material
+//! that hangs and steer clear of a repetition when it is winning.
+
+use std::sync::atomic::AtomicBool;
+
+use chess::search::{Engine, Limits, Score};
+use chess::{Move, Position};
+
+fn best(fen: &str, depth: u32) -> (Move, Score) {
+    search(&Position::from_fen(fen).unwrap(), &[], depth)
+}
+
+/// Searches `position`, reached through the positions hashed in `history`.
+fn search(position: &Position, history: &[u64], depth: u32) -> (Move, Score) {
+    let limits = Limits {
+        depth: Some(depth),
+        ..Limits::default()
+    };
+
+    let mut score = None;
+    let mv = Engine::new(1)
+        .search(
+            position,
+            history,
+            &limits,
+            &AtomicBool::new(false),
+            |info| score = Some(info.score),
+        )
+        .unwrap();
+
+    (mv, score.unwrap())
+}
+
+#[test]
+fn mate_in_one() {
+    let (mv, score) = best("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 3);
+
+    assert_eq!(mv.to_uci(), "a1a8");
+    assert_eq!(score, Score::Mate(1));
+}
+
+#[test]
+fn mate_in_two() {
+    // A rook cuts the king off on the seventh, the other mates on the eighth
+    let (_, score) = best("7k/8/8/8/8/8/8/RR4K1 w - - 0 1", 4);
+
+    assert_eq!(score, Score::Mate(2));
+}
+
+#[test]
+fn takes_a_hanging_queen() {
+    let (mv, _) = best("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 3);
+
+    assert_eq!(mv.to_uci(), "d1d5");
+}
+
+#[test]
+fn sees_a_defended_piece() {
+    // The knight on d5 is guarded by a pawn, so taking it loses the rook
+    let (mv, _) = best("4k3/8/4p3/3n4/8/8/8/3RK3 w - - 0 1", 4);
+
+    assert_ne!(mv.to_uci(), "d1d5");
+}
+
+#[test]
+fn avoids_a_repetition_when_winning() {
+    let start = Position::from_fen("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1").unwrap();
+    let (natural, _) = search(&start, &[], 5);
+    assert_eq!(natural.to_uci(), "d2d5");
+
+    // Shuffle back to the start twice, so that the same move again would
+    // make its position occur for the third time
+    let mut position = start.clone();
+    let mut history = vec![];
+
+    for uci in ["d2d5", "e8e7", "d5d2", "e7e8"].repeat(2) {
+        history.push(position.hash());
+        let mv = position.parse_uci(uci).unwrap();
+        position.make_move(mv);
+    }
+
+    assert_eq!(position.hash(), start.hash());
+
+    let (mv, score) = search(&position, &history, 5);
+    assert_ne!(mv, natural);
+    assert!(
+        matches!(score, Score::Centipawns(cp) if cp > 500),
+        "{score:?}"
+    );
+}
+
+#[test]
+fn no_moves_no_best_move() {
+    let position = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    let best = Engine::new(1).search(
+        &position,
+        &[],
+        &Limits::default(),
+        &AtomicBool::new(false),
+        |_| {},
+    );
+
+    assert_eq!(best, None);
+}
+
+#[test]
+fn stop_flag_ends_an_infinite_search() {
+    let position = Position::start();
+    let limits = Limits {
+        infinite: true,
+        ..Limits::default()
+    };
+
+    let best = Engine::new(1).search(&position, &[], &limits, &AtomicBool::new(true), |_| {});
+
+    assert!(best.is_some());
+}
@@ -0,0 +1,123 @@
+//! Drives `chess --uci` through scripted stdin, as a GUI would.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use chess::Position;
+
+/// Runs the engine on `script` and returns everything it printed.
+fn run(script: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chess"))
+        .arg("--uci")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn bestmoves(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("bestmove "))
+        .collect()
+}
+
+#[test]
+fn handshake() {
+    let output = run("uci\nisready\nquit\n");
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert!(lines[0].starts_with("id name "));
+    assert!(lines.contains(&"uciok"));
+    assert_eq!(lines.last(), Some(&"readyok"));
+}
+
+#[test]
+fn go_depth_plays_a_legal_move() {
+    let output = run("position startpos moves e2e4 e7e5\ngo depth 3\n");
+
+    let position =
+        Position::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2").unwrap();
+    let best = bestmoves(&output);
+
+    assert_eq!(best.len(), 1);
+    assert!(position.parse_uci(best[0]).is_ok(), "{output}");
+    assert!(output.contains("info depth 3 "));
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let output = run("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo movetime 1000\n");
+
+    assert_eq!(bestmoves(&output), ["a1a8"]);
+    assert!(output.contains("score mate 1"));
+}
+
+#[test]
+fn infinite_waits_for_stop() {
+    let output = run("position startpos\ngo infinite\nisready\nstop\nquit\n");
+    let lines: Vec<&str> = output.lines().collect();
+
+    let ready = lines.iter().position(|&l| l == "readyok").unwrap();
+    let best = lines
+        .iter()
+        .position(|l| l.starts_with("bestmove"))
+        .unwrap();
+
+    assert!(ready < best);
+}
+
+#[test]
+fn clock_limits_end_the_search() {
+    let output = run("position startpos moves e2e4\ngo wtime 1000 btime 1000 winc 10 binc 10\n");
+
+    assert_eq!(bestmoves(&output).len(), 1);
+}
+
+#[test]
+fn bad_input_is_reported() {
+    let output = run("position startpos moves e2e5\nsetoption name Nonsense value 1\nfoo\nquit\n");
+
+    assert_eq!(
+        output
+            .lines()
+            .filter(|l| l.starts_with("info string"))
+            .count(),
+        3
+    );
+}
+
+#[test]
+fn malformed_options_are_survived() {
+    // A value with no name, a bare `setoption`, and a table too big to
+    // allocate, which is clamped to the largest size offered
+    let output = run(
+        "setoption value 5\nsetoption\nsetoption name Hash value 100000000\n\
+         isready\nposition startpos\ngo depth 2\n",
+    );
+
+    assert!(output.lines().any(|l| l == "readyok"));
+    assert_eq!(bestmoves(&output).len(), 1);
+}
+
+#[test]
+fn move_overhead_is_clamped() {
+    // Kept back in full, this much would leave no time to search at all
+    let output = run("setoption name Move Overhead value 4000000000\n\
+         position startpos\ngo wtime 60000 btime 60000\n");
+
+    assert_eq!(bestmoves(&output).len(), 1);
+    assert!(output.contains("info depth 2 "), "{output}");
+}